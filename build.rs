@@ -188,7 +188,7 @@ fn generate_bindings(features: Features, out_dir: &str) {
                     }
                 }
                 if features.osmesa {
-                    native_include.push_str("\n#define GLFW_EXPOSE_NATIVE_OS_MESA\n");
+                    native_include.push_str("\n#define GLFW_EXPOSE_NATIVE_OSMESA\n");
                 }
             }
         };
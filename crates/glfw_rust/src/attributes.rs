@@ -0,0 +1,262 @@
+use crate::ffi::*;
+use crate::*;
+
+/// A snapshot of all the attributes of a [Window]. Get it with [Window::attributes].
+///
+/// This is useful to display (or diff) the window state in tools/editors.
+/// Use [WindowAttributes::diff] to get a [WindowAttributesPatch], which can be applied
+/// to a window with [Window::apply].
+///
+/// <https://www.glfw.org/docs/latest/window_guide.html#window_attribs>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowAttributes {
+    /// see [Window::get_focused]
+    pub focused: bool,
+    /// see [Window::get_iconified]
+    pub iconified: bool,
+    /// see [Window::get_maximized]
+    pub maximized: bool,
+    /// see [Window::get_hovered]
+    pub hovered: bool,
+    /// see [Window::get_visible]
+    pub visible: bool,
+    /// see [Window::get_resizeable]
+    pub resizeable: bool,
+    /// see [Window::get_decorated]
+    pub decorated: bool,
+    /// see [Window::get_auto_iconify]
+    pub auto_iconify: bool,
+    /// see [Window::get_floating]
+    pub floating: bool,
+    /// see [Window::get_transparent_framebuffer]
+    pub transparent_framebuffer: bool,
+    /// see [Window::get_focus_on_show]
+    pub focus_on_show: bool,
+    /// see [Window::get_mouse_passthrough]
+    pub mouse_passthrough: bool,
+    /// see [Window::get_doublebuffer]
+    pub doublebuffer: bool,
+    /// see [Window::get_client_api]
+    pub client_api: ClientApi,
+    /// see [Window::get_context_creation_api]
+    ///
+    /// None if the window doesn't have a context ([ClientApi::NoAPI]).
+    pub context_creation_api: Option<ContextCreationApi>,
+    /// see [Window::get_context_version_major]
+    pub context_version_major: i32,
+    /// see [Window::get_context_version_minor]
+    pub context_version_minor: i32,
+    /// see [Window::get_context_revision]
+    pub context_revision: i32,
+    /// see [Window::get_opengl_forward_compat]
+    pub opengl_forward_compat: bool,
+    /// see [Window::get_context_debug]
+    pub context_debug: bool,
+    /// see [Window::get_opengl_profile]
+    pub opengl_profile: Option<OpenGLProfile>,
+    /// see [Window::get_context_release_behavior]
+    pub context_release_behavior: Option<ContextReleaseBehavior>,
+    /// see [Window::get_context_no_error]
+    pub context_no_error: bool,
+    /// see [Window::get_context_robustness]
+    pub context_robustness: Option<Robustness>,
+}
+/// The subset of [WindowAttributes] that can be changed after window creation.
+///
+/// Fields set to `None` are left unchanged by [Window::apply].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowAttributesPatch {
+    /// see [Window::set_resizeable]
+    pub resizeable: Option<bool>,
+    /// see [Window::set_decorated]
+    pub decorated: Option<bool>,
+    /// see [Window::set_auto_iconify]
+    pub auto_iconify: Option<bool>,
+    /// see [Window::set_floating]
+    pub floating: Option<bool>,
+    /// see [Window::set_focus_on_show]
+    pub focus_on_show: Option<bool>,
+    /// see [Window::set_mouse_passthrough]
+    pub mouse_passthrough: Option<bool>,
+}
+impl WindowAttributes {
+    /// Returns a patch with the mutable attributes of `target` that differ from `self`.
+    ///
+    /// Applying the patch to a window with the attributes of `self` will make its
+    /// mutable attributes the same as `target`.
+    pub fn diff(&self, target: &WindowAttributes) -> WindowAttributesPatch {
+        let changed = |old: bool, new: bool| (old != new).then_some(new);
+        WindowAttributesPatch {
+            resizeable: changed(self.resizeable, target.resizeable),
+            decorated: changed(self.decorated, target.decorated),
+            auto_iconify: changed(self.auto_iconify, target.auto_iconify),
+            floating: changed(self.floating, target.floating),
+            focus_on_show: changed(self.focus_on_show, target.focus_on_show),
+            mouse_passthrough: changed(self.mouse_passthrough, target.mouse_passthrough),
+        }
+    }
+}
+impl WindowAttributesPatch {
+    /// Returns true if the patch doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+impl Window {
+    /// Queries all the window attributes in one go.
+    ///
+    /// Unlike the individual getters (like [Window::get_focused]), this checks for errors.
+    /// If any of the queries failed, this returns the last error reported by glfw.
+    ///
+    /// Returns [CrateError::InvalidWindowAttribute] if glfw returned an unknown client api.
+    #[doc(alias = "glfwGetWindowAttrib")]
+    pub fn attributes(&self) -> GlfwResult<WindowAttributes> {
+        let window = self.id().get_ptr();
+        let el: &EventLoop = self.as_ref();
+        let attrib = |attrib: i32| unsafe { glfwGetWindowAttrib(window, attrib) };
+        let flag = |name: i32| attrib(name) == GLFW_TRUE;
+        let client_api = el.checked(|| attrib(GLFW_CLIENT_API))?;
+        let client_api = client_api.try_into().map_err(|_| GlfwError {
+            code: ErrorCode::Crate(CrateError::InvalidWindowAttribute),
+            description: format!("glfw-rust: invalid client api {client_api}"),
+            context: None,
+        })?;
+        el.checked(|| WindowAttributes {
+            focused: flag(GLFW_FOCUSED),
+            iconified: flag(GLFW_ICONIFIED),
+            maximized: flag(GLFW_MAXIMIZED),
+            hovered: flag(GLFW_HOVERED),
+            visible: flag(GLFW_VISIBLE),
+            resizeable: flag(GLFW_RESIZABLE),
+            decorated: flag(GLFW_DECORATED),
+            auto_iconify: flag(GLFW_AUTO_ICONIFY),
+            floating: flag(GLFW_FLOATING),
+            transparent_framebuffer: flag(GLFW_TRANSPARENT_FRAMEBUFFER),
+            focus_on_show: flag(GLFW_FOCUS_ON_SHOW),
+            mouse_passthrough: flag(GLFW_MOUSE_PASSTHROUGH),
+            doublebuffer: flag(GLFW_DOUBLEBUFFER),
+            client_api,
+            context_creation_api: attrib(GLFW_CONTEXT_CREATION_API).try_into().ok(),
+            context_version_major: attrib(GLFW_CONTEXT_VERSION_MAJOR),
+            context_version_minor: attrib(GLFW_CONTEXT_VERSION_MINOR),
+            context_revision: attrib(GLFW_CONTEXT_REVISION),
+            opengl_forward_compat: flag(GLFW_OPENGL_FORWARD_COMPAT),
+            context_debug: flag(GLFW_CONTEXT_DEBUG),
+            opengl_profile: attrib(GLFW_OPENGL_PROFILE).try_into().ok(),
+            context_release_behavior: attrib(GLFW_CONTEXT_RELEASE_BEHAVIOR).try_into().ok(),
+            context_no_error: flag(GLFW_CONTEXT_NO_ERROR),
+            context_robustness: attrib(GLFW_CONTEXT_ROBUSTNESS).try_into().ok(),
+        })
+    }
+    /// Sets all the attributes in the patch that are not `None`.
+    ///
+    /// Returns the first error if any of the attributes failed to be set.
+    /// The rest of the attributes are still set.
+    #[doc(alias = "glfwSetWindowAttrib")]
+    pub fn apply(&self, patch: WindowAttributesPatch) -> GlfwResult<()> {
+        let WindowAttributesPatch {
+            resizeable,
+            decorated,
+            auto_iconify,
+            floating,
+            focus_on_show,
+            mouse_passthrough,
+        } = patch;
+        let window = self.id().get_ptr();
+        let el: &EventLoop = self.as_ref();
+        let mut result = Ok(());
+        for (attrib, value) in [
+            (GLFW_RESIZABLE, resizeable),
+            (GLFW_DECORATED, decorated),
+            (GLFW_AUTO_ICONIFY, auto_iconify),
+            (GLFW_FLOATING, floating),
+            (GLFW_FOCUS_ON_SHOW, focus_on_show),
+            (GLFW_MOUSE_PASSTHROUGH, mouse_passthrough),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+            let set_result =
+                el.checked(|| unsafe { glfwSetWindowAttrib(window, attrib, bool_to_glfw(value)) });
+            if result.is_ok() {
+                result = set_result;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn attributes() -> WindowAttributes {
+        WindowAttributes {
+            focused: true,
+            iconified: false,
+            maximized: false,
+            hovered: false,
+            visible: true,
+            resizeable: true,
+            decorated: true,
+            auto_iconify: true,
+            floating: false,
+            transparent_framebuffer: false,
+            focus_on_show: true,
+            mouse_passthrough: false,
+            doublebuffer: true,
+            client_api: ClientApi::OpenGL,
+            context_creation_api: Some(ContextCreationApi::Native),
+            context_version_major: 3,
+            context_version_minor: 3,
+            context_revision: 0,
+            opengl_forward_compat: false,
+            context_debug: false,
+            opengl_profile: Some(OpenGLProfile::Core),
+            context_release_behavior: None,
+            context_no_error: false,
+            context_robustness: Some(Robustness::No),
+        }
+    }
+    #[test]
+    fn diff_only_mutable_changes() {
+        let old = attributes();
+        assert!(old.diff(&old).is_empty());
+
+        // read-only attributes are not part of the patch
+        let target = WindowAttributes {
+            focused: false,
+            maximized: true,
+            context_version_major: 4,
+            ..old
+        };
+        assert!(old.diff(&target).is_empty());
+
+        let target = WindowAttributes {
+            resizeable: false,
+            floating: true,
+            mouse_passthrough: true,
+            ..old
+        };
+        let patch = old.diff(&target);
+        assert!(!patch.is_empty());
+        assert_eq!(
+            patch,
+            WindowAttributesPatch {
+                resizeable: Some(false),
+                floating: Some(true),
+                mouse_passthrough: Some(true),
+                ..Default::default()
+            }
+        );
+        // diffing back undoes the patch
+        assert_eq!(
+            target.diff(&old),
+            WindowAttributesPatch {
+                resizeable: Some(true),
+                floating: Some(false),
+                mouse_passthrough: Some(false),
+                ..Default::default()
+            }
+        );
+    }
+}
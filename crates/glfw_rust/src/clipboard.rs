@@ -0,0 +1,89 @@
+use crate::ffi::*;
+use crate::*;
+
+/// The system clipboard. Get it with [EventLoop::clipboard].
+///
+/// Unlike [Window::get_clipboard_string], this doesn't need a window, so it also works in
+/// headless tools or when no window is focused.
+///
+/// Glfw has no clipboard change notifications, so use [Clipboard::poll_changed] once in a
+/// while (eg: every frame or on focus) to detect changes made by other apps.
+#[derive(Debug)]
+pub struct Clipboard<'a> {
+    el: &'a EventLoop,
+    /// The text seen by the last [Clipboard::poll_changed] call.
+    last_seen: Option<String>,
+}
+impl EventLoop {
+    /// Returns a handle to the system clipboard.
+    pub fn clipboard(&self) -> Clipboard<'_> {
+        Clipboard {
+            el: self,
+            last_seen: None,
+        }
+    }
+}
+impl Clipboard<'_> {
+    /// Returns the contents of the clipboard, if it contains or is convertible to a UTF-8 string.
+    ///
+    /// Returns `Ok(None)` if the clipboard is empty or its contents can't be converted.
+    /// glfw reports that as [ErrorCode::FormatUnavailable], but as it is not a failure, that
+    /// error doesn't reach [EventLoopConfig::on_error] or [EventLoop::recent_errors].
+    #[doc(alias = "glfwGetClipboardString")]
+    pub fn get_text(&self) -> GlfwResult<Option<String>> {
+        let text = self
+            .el
+            .checked_expecting(ErrorCode::FormatUnavailable, || unsafe {
+                clipboard_text(glfwGetClipboardString(std::ptr::null_mut()))
+            })?;
+        Ok(text.flatten())
+    }
+    /// Sets the clipboard to `text`.
+    ///
+    /// Returns [CrateError::InteriorNul] if the text contains a null byte.
+    #[doc(alias = "glfwSetClipboardString")]
+    pub fn set_text(&mut self, text: &str) -> GlfwResult<()> {
+        let c_text = CString::new(text).map_err(|_| GlfwError {
+            code: ErrorCode::Crate(CrateError::InteriorNul),
+            description: "glfw-rust: clipboard text contains a null byte".to_string(),
+            context: None,
+        })?;
+        self.el.checked(|| unsafe {
+            glfwSetClipboardString(std::ptr::null_mut(), c_text.as_ptr());
+        })?;
+        // we don't want to report our own changes in poll_changed
+        self.last_seen = Some(text.to_string());
+        Ok(())
+    }
+    /// Returns the new text if the clipboard changed since the last call
+    /// (or since [Self::set_text]). The first call reports the current text as a change.
+    ///
+    /// Returns `Ok(None)` if nothing changed. If the clipboard became empty
+    /// (or non-text), that is reported as `Some(String::new())`.
+    pub fn poll_changed(&mut self) -> GlfwResult<Option<String>> {
+        let text = self.get_text()?.unwrap_or_default();
+        if self.last_seen.as_ref() == Some(&text) {
+            return Ok(None);
+        }
+        self.last_seen = Some(text.clone());
+        Ok(Some(text))
+    }
+}
+/// Copies the string returned by `glfwGetClipboardString` or `glfwGetX11SelectionString`.
+///
+/// glfw returns null if the clipboard is empty or can't be converted to a string.
+///
+/// # Safety
+/// `text` must be null or a valid null-terminated string.
+pub(crate) unsafe fn clipboard_text(text: *const std::ffi::c_char) -> Option<String> {
+    (!text.is_null()).then(|| CStr::from_ptr(text).to_string_lossy().to_string())
+}
+/// The error returned by [Window::get_clipboard_string] and [EventLoop::get_primary_selection],
+/// if glfw returned null without reporting an error.
+pub(crate) fn empty_clipboard(what: &str) -> GlfwError {
+    GlfwError {
+        code: ErrorCode::FormatUnavailable,
+        description: format!("glfw-rust: the {what} is empty"),
+        context: None,
+    }
+}
@@ -0,0 +1,1065 @@
+//! Helpers for the OpenGL (ES) context of a window, which need to call gl functions.
+//!
+//! We don't want to depend on any gl bindings crate, so we just load the few
+//! functions we need with [WindowProxy::get_proc_addr_cstr] and declare the
+//! few constants we need below.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::ffi::{glfwCreateWindow, glfwDestroyWindow};
+#[cfg(feature = "osmesa")]
+use crate::ffi::{glfwGetOSMesaColorBuffer, glfwGetOSMesaDepthBuffer};
+use crate::*;
+
+const GL_VENDOR: u32 = 0x1F00;
+const GL_RENDERER: u32 = 0x1F01;
+const GL_VERSION: u32 = 0x1F02;
+const GL_EXTENSIONS: u32 = 0x1F03;
+const GL_SHADING_LANGUAGE_VERSION: u32 = 0x8B8C;
+const GL_NUM_EXTENSIONS: u32 = 0x821D;
+const GL_CONTEXT_PROFILE_MASK: u32 = 0x9126;
+const GL_CONTEXT_CORE_PROFILE_BIT: i32 = 0x1;
+const GL_CONTEXT_COMPATIBILITY_PROFILE_BIT: i32 = 0x2;
+
+type GlGetString = unsafe extern "system" fn(name: u32) -> *const u8;
+type GlGetStringi = unsafe extern "system" fn(name: u32, index: u32) -> *const u8;
+type GlGetIntegerv = unsafe extern "system" fn(name: u32, data: *mut i32);
+
+/// Details about the OpenGL (ES) context of a window, queried from the context itself
+/// using `glGetString`/`glGetStringi`/`glGetIntegerv`.
+///
+/// Get it with [WindowProxy::context_info].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContextInfo {
+    /// `GL_VENDOR`. eg: "Mesa"
+    pub vendor: String,
+    /// `GL_RENDERER`. eg: "llvmpipe (LLVM 15.0.7, 256 bits)"
+    pub renderer: String,
+    /// `GL_VERSION`. eg: "4.5 (Core Profile) Mesa 23.0.4" or "OpenGL ES 3.2 Mesa 23.0.4"
+    pub version: String,
+    /// `GL_SHADING_LANGUAGE_VERSION`.
+    ///
+    /// None on contexts without shaders (eg: OpenGL ES 1.x)
+    pub glsl_version: Option<String>,
+    /// true if the context is OpenGL ES.
+    pub is_gles: bool,
+    /// major version parsed from [Self::version]
+    pub version_major: u32,
+    /// minor version parsed from [Self::version]
+    pub version_minor: u32,
+    /// The profile of the context.
+    ///
+    /// [OpenGLProfile::Any] for OpenGL ES and OpenGL versions below 3.2, which don't have profiles.
+    pub profile: OpenGLProfile,
+    /// The robustness (reset notification strategy) of the context.
+    ///
+    /// This is the value glfw detected when creating the context, same as
+    /// [Window::get_context_robustness].
+    pub robustness: Robustness,
+    /// All the extensions supported by the context.
+    ///
+    /// This doesn't include the context creation API extensions (eg: `WGL_*` or `GLX_*`).
+    /// Use [WindowProxy::extension_supported] for those.
+    pub extensions: Vec<String>,
+}
+impl ContextInfo {
+    /// Returns true if the context version is at least `major.minor`.
+    pub fn version_at_least(&self, major: u32, minor: u32) -> bool {
+        (self.version_major, self.version_minor) >= (major, minor)
+    }
+    /// Returns true if [Self::extensions] contains `extension`.
+    pub fn has_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+    }
+}
+/// Parses the major and minor version from the `GL_VERSION` string.
+///
+/// The string starts with "major.minor" for OpenGL and "OpenGL ES major.minor" for OpenGL ES.
+/// Some OpenGL ES 1.x implementations use "OpenGL ES-CM 1.1".
+fn parse_gl_version(version: &str) -> (bool, u32, u32) {
+    let is_gles = version.starts_with("OpenGL ES");
+    let number = version
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .unwrap_or_default();
+    let mut parts = number
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse().unwrap_or_default());
+    let major = parts.next().unwrap_or_default();
+    let minor = parts.next().unwrap_or_default();
+    (is_gles, major, minor)
+}
+impl WindowProxy {
+    /// Loads a gl function with [Self::get_proc_addr_cstr] and transmutes it into `F`.
+    ///
+    /// # Safety
+    /// `F` must be an `extern "system"` function pointer type with the correct signature for `name`.
+    unsafe fn load_gl_fn<F: Copy>(&self, name: &CStr) -> Option<F> {
+        assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<*mut c_void>());
+        let ptr = self.get_proc_addr_cstr(name);
+        (!ptr.is_null()).then(|| unsafe { std::mem::transmute_copy::<*mut c_void, F>(&ptr) })
+    }
+    /// Returns the [ContextInfo] of the window's OpenGL (ES) context.
+    ///
+    /// The info is queried the first time this is called and cached for the lifetime of
+    /// the window, as it doesn't change during the lifetime of a context.
+    ///
+    /// This only needs a working gl implementation, so it also works with software
+    /// renderers like Mesa's llvmpipe or OSMesa on machines without a GPU.
+    ///
+    /// # Panics
+    /// * if the window is not current on the calling thread
+    /// * if the context doesn't provide `glGetString` or `glGetIntegerv`
+    #[doc(alias = "glGetString")]
+    pub fn context_info(&self) -> &ContextInfo {
+        assert!(self.is_current_on_current_thread());
+        self.data
+            .context_info
+            .get_or_init(|| unsafe { self.query_context_info() })
+    }
+    /// Queries the [ContextInfo] from the context.
+    ///
+    /// # Safety
+    /// The window must be current on the calling thread.
+    unsafe fn query_context_info(&self) -> ContextInfo {
+        let get_string: GlGetString = self
+            .load_gl_fn(c"glGetString")
+            .expect("failed to load glGetString");
+        let get_integer: GlGetIntegerv = self
+            .load_gl_fn(c"glGetIntegerv")
+            .expect("failed to load glGetIntegerv");
+        let string = |name: u32| -> Option<String> {
+            let p = get_string(name);
+            (!p.is_null()).then(|| CStr::from_ptr(p.cast()).to_string_lossy().into_owned())
+        };
+        let integer = |name: u32| -> i32 {
+            let mut value = 0;
+            get_integer(name, &mut value);
+            value
+        };
+        let version = string(GL_VERSION).unwrap_or_default();
+        let (is_gles, version_major, version_minor) = parse_gl_version(&version);
+        // glGetStringi and GL_NUM_EXTENSIONS are available since OpenGL (ES) 3.0.
+        // core profile contexts don't support glGetString(GL_EXTENSIONS) anymore.
+        let get_stringi: Option<GlGetStringi> = if version_major >= 3 {
+            self.load_gl_fn(c"glGetStringi")
+        } else {
+            None
+        };
+        let extensions: Vec<String> = match get_stringi {
+            Some(get_stringi) => (0..integer(GL_NUM_EXTENSIONS).max(0) as u32)
+                .filter_map(|index| {
+                    let p = get_stringi(GL_EXTENSIONS, index);
+                    (!p.is_null()).then(|| CStr::from_ptr(p.cast()).to_string_lossy().into_owned())
+                })
+                .collect(),
+            None => string(GL_EXTENSIONS)
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        };
+        let profile = if !is_gles && (version_major, version_minor) >= (3, 2) {
+            let mask = integer(GL_CONTEXT_PROFILE_MASK);
+            if mask & GL_CONTEXT_CORE_PROFILE_BIT != 0 {
+                OpenGLProfile::Core
+            } else if mask & GL_CONTEXT_COMPATIBILITY_PROFILE_BIT != 0 {
+                OpenGLProfile::Compatibility
+            } else {
+                OpenGLProfile::Any
+            }
+        } else {
+            OpenGLProfile::Any
+        };
+        ContextInfo {
+            vendor: string(GL_VENDOR).unwrap_or_default(),
+            renderer: string(GL_RENDERER).unwrap_or_default(),
+            glsl_version: string(GL_SHADING_LANGUAGE_VERSION),
+            is_gles,
+            version_major,
+            version_minor,
+            version,
+            profile,
+            // glfw already checked the extensions/version and queried the reset strategy.
+            robustness: self.data.context_robustness,
+            extensions,
+        }
+    }
+}
+
+const GL_DEBUG_OUTPUT: u32 = 0x92E0;
+const GL_DEBUG_OUTPUT_SYNCHRONOUS: u32 = 0x8242;
+
+type GlEnable = unsafe extern "system" fn(cap: u32);
+type GlDebugProc = unsafe extern "system" fn(
+    source: u32,
+    kind: u32,
+    id: u32,
+    severity: u32,
+    length: i32,
+    message: *const std::ffi::c_char,
+    user_param: *mut c_void,
+);
+type GlDebugMessageCallback =
+    unsafe extern "system" fn(callback: Option<GlDebugProc>, user_param: *const c_void);
+
+/// The severity of a debug message from [WindowProxy::enable_gl_debug_output].
+///
+/// Ordered from least to most severe, so that you can compare with [GlDebugFilter::min_severity].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GlDebugSeverity {
+    /// `GL_DEBUG_SEVERITY_NOTIFICATION`. logged with [tracing::debug]
+    Notification,
+    /// `GL_DEBUG_SEVERITY_LOW`. logged with [tracing::info]
+    Low,
+    /// `GL_DEBUG_SEVERITY_MEDIUM`. logged with [tracing::warn]
+    Medium,
+    /// `GL_DEBUG_SEVERITY_HIGH`. logged with [tracing::error]
+    High,
+}
+impl GlDebugSeverity {
+    fn from_gl(severity: u32) -> Self {
+        match severity {
+            0x9146 => Self::High,
+            0x9147 => Self::Medium,
+            0x9148 => Self::Low,
+            _ => Self::Notification,
+        }
+    }
+}
+/// `GL_DEBUG_SOURCE_*` as a string, to be used as a tracing field.
+fn gl_debug_source(source: u32) -> &'static str {
+    match source {
+        0x8246 => "api",
+        0x8247 => "window_system",
+        0x8248 => "shader_compiler",
+        0x8249 => "third_party",
+        0x824A => "application",
+        _ => "other",
+    }
+}
+/// `GL_DEBUG_TYPE_*` as a string, to be used as a tracing field.
+fn gl_debug_type(kind: u32) -> &'static str {
+    match kind {
+        0x824C => "error",
+        0x824D => "deprecated_behavior",
+        0x824E => "undefined_behavior",
+        0x824F => "portability",
+        0x8250 => "performance",
+        0x8268 => "marker",
+        0x8269 => "push_group",
+        0x826A => "pop_group",
+        _ => "other",
+    }
+}
+/// Decides which gl debug messages are forwarded to `tracing` by [WindowProxy::enable_gl_debug_output].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GlDebugFilter {
+    /// Messages less severe than this are ignored.
+    ///
+    /// default: [GlDebugSeverity::Low], as notifications tend to be very noisy.
+    pub min_severity: GlDebugSeverity,
+    /// Enables `GL_DEBUG_OUTPUT_SYNCHRONOUS`, so that the messages are logged
+    /// on the thread (and inside the gl call) that caused them.
+    /// This makes the span/backtrace of the log useful, but can be slow.
+    ///
+    /// default: true
+    pub synchronous: bool,
+    /// Message ids to ignore. Useful for silencing known driver spam
+    /// (eg: nvidia's buffer detailed info `131185`).
+    ///
+    /// default: empty
+    pub blocked_ids: Vec<u32>,
+}
+impl Default for GlDebugFilter {
+    fn default() -> Self {
+        Self {
+            min_severity: GlDebugSeverity::Low,
+            synchronous: true,
+            blocked_ids: vec![],
+        }
+    }
+}
+/// The callback given to `glDebugMessageCallback`. `user_param` is a [GlDebugFilter]
+/// stored in [WindowData::gl_debug_filters].
+unsafe extern "system" fn gl_debug_callback(
+    source: u32,
+    kind: u32,
+    id: u32,
+    severity: u32,
+    length: i32,
+    message: *const std::ffi::c_char,
+    user_param: *mut c_void,
+) {
+    let filter = &*(user_param as *const GlDebugFilter);
+    let severity = GlDebugSeverity::from_gl(severity);
+    if severity < filter.min_severity || filter.blocked_ids.contains(&id) || message.is_null() {
+        return;
+    }
+    let message = if length >= 0 {
+        String::from_utf8_lossy(std::slice::from_raw_parts(message.cast(), length as usize))
+    } else {
+        CStr::from_ptr(message).to_string_lossy()
+    };
+    let source = gl_debug_source(source);
+    let kind = gl_debug_type(kind);
+    match severity {
+        GlDebugSeverity::High => tracing::error!(target: "gl_debug", source, kind, id, "{message}"),
+        GlDebugSeverity::Medium => {
+            tracing::warn!(target: "gl_debug", source, kind, id, "{message}")
+        }
+        GlDebugSeverity::Low => tracing::info!(target: "gl_debug", source, kind, id, "{message}"),
+        GlDebugSeverity::Notification => {
+            tracing::debug!(target: "gl_debug", source, kind, id, "{message}")
+        }
+    }
+}
+impl WindowProxy {
+    /// Enables `GL_DEBUG_OUTPUT` and forwards the debug messages of the context to `tracing`
+    /// (with target `gl_debug`). The severity decides the level (see [GlDebugSeverity]) and
+    /// the source/type/id of the message are added as fields.
+    ///
+    /// You usually want to create the window with [WindowConfig::opengl_context_debug] set to true,
+    /// as non-debug contexts may not produce any messages.
+    ///
+    /// Calling this again replaces the previous filter. The replaced filters are kept in memory
+    /// until the window is destroyed, as asynchronous debug output may still be using them.
+    ///
+    /// Returns [ErrorCode::FeatureUnavailable] if the context doesn't support debug output
+    /// (needs OpenGL 4.3, OpenGL ES 3.2, `GL_KHR_debug` or `GL_ARB_debug_output`).
+    ///
+    /// # Panics
+    /// * if the window is not current on the calling thread
+    #[doc(alias = "glDebugMessageCallback")]
+    pub fn enable_gl_debug_output(&self, filter: GlDebugFilter) -> GlfwResult<()> {
+        let info = self.context_info();
+        // debug output is core since OpenGL 4.3 and OpenGL ES 3.2
+        let (core_major, core_minor) = if info.is_gles { (3, 2) } else { (4, 3) };
+        let (callback_name, is_arb): (&CStr, bool) =
+            if info.version_at_least(core_major, core_minor) {
+                (c"glDebugMessageCallback", false)
+            } else if info.has_extension("GL_KHR_debug") {
+                if info.is_gles {
+                    (c"glDebugMessageCallbackKHR", false)
+                } else {
+                    (c"glDebugMessageCallback", false)
+                }
+            } else if info.has_extension("GL_ARB_debug_output") {
+                (c"glDebugMessageCallbackARB", true)
+            } else {
+                return Err(GlfwError {
+                    code: ErrorCode::FeatureUnavailable,
+                    description: "glfw-rust: the context doesn't support debug output".to_string(),
+                    context: None,
+                });
+            };
+        let unavailable = |name: &CStr| GlfwError {
+            code: ErrorCode::FeatureUnavailable,
+            description: format!("glfw-rust: failed to load {name:?}"),
+            context: None,
+        };
+        unsafe {
+            let set_callback: GlDebugMessageCallback = self
+                .load_gl_fn(callback_name)
+                .ok_or_else(|| unavailable(callback_name))?;
+            let enable: GlEnable = self
+                .load_gl_fn(c"glEnable")
+                .ok_or_else(|| unavailable(c"glEnable"))?;
+            let disable: GlEnable = self
+                .load_gl_fn(c"glDisable")
+                .ok_or_else(|| unavailable(c"glDisable"))?;
+            let synchronous = filter.synchronous;
+            let filter = Arc::new(filter);
+            // never free the previous filters: with asynchronous output, a driver thread may
+            // still be inside the callback with the old pointer after the new one is installed.
+            let mut filters = self.data.gl_debug_filters.lock().unwrap();
+            set_callback(Some(gl_debug_callback), Arc::as_ptr(&filter).cast());
+            filters.push(filter);
+            // ARB_debug_output has no GL_DEBUG_OUTPUT toggle, it is always on in debug contexts.
+            if !is_arb {
+                enable(GL_DEBUG_OUTPUT);
+            }
+            if synchronous {
+                enable(GL_DEBUG_OUTPUT_SYNCHRONOUS);
+            } else {
+                disable(GL_DEBUG_OUTPUT_SYNCHRONOUS);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A guard returned by [WindowProxy::make_current_scoped], which makes the window current on this
+/// thread until it is dropped.
+///
+/// When dropped, the context that was current before the guard was created (if any) is made
+/// current again. Guards can be nested. If an outer guard is dropped before an inner one, the
+/// inner guard takes over restoring the context that was current before the outer guard.
+/// So, once all the guards of a thread are dropped, the context that was current before the
+/// first one is current again. If that context was destroyed or made current on another
+/// thread in the meantime, the window is just made uncurrent.
+///
+/// The guard is not `Send`, as the window is only current on the thread that created it.
+/// The functions that need a current context, like [Self::get_proc_addr] or
+/// [Self::set_swap_interval], are only implemented on the guard, so that they are called
+/// while the window is current. This is *not* a compile time guarantee though: the guard only
+/// borrows the [WindowProxy], so [WindowProxy::make_current], [WindowProxy::make_uncurrent]
+/// (on any proxy) or [EventLoopProxy::make_any_uncurrent] can still change the current
+/// context while the guard is alive. After that, those functions of the guard panic,
+/// as the window is not current on the calling thread anymore.
+/// Don't mix those functions with guards on the same thread.
+#[must_use = "the window is made uncurrent again when the guard is dropped"]
+pub struct CurrentGuard<'a> {
+    window: &'a WindowProxy,
+    /// identifies the entry of this guard in [CURRENT_GUARDS].
+    id: u64,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+/// A [CurrentGuard] that is alive on this thread.
+struct CurrentGuardEntry {
+    id: u64,
+    /// The context to make current again, when the guard is dropped.
+    previous: Option<Arc<WindowData>>,
+}
+thread_local! {
+    /// The [CurrentGuard]s alive on this thread, from the outermost to the innermost.
+    static CURRENT_GUARDS: RefCell<Vec<CurrentGuardEntry>> = const { RefCell::new(Vec::new()) };
+}
+/// The id of the next [CurrentGuard].
+static NEXT_CURRENT_GUARD_ID: AtomicU64 = AtomicU64::new(0);
+impl std::fmt::Debug for CurrentGuard<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CurrentGuard")
+            .field("window", &self.window.id())
+            .field("id", &self.id)
+            .finish()
+    }
+}
+impl CurrentGuard<'_> {
+    /// The id of the window that is current.
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+    /// This function swaps the front and back buffers of the window
+    /// when rendering with OpenGL or OpenGL ES. If the swap interval is greater
+    /// than zero, the GPU driver waits the specified number of screen updates
+    /// before swapping the buffers.
+    ///
+    /// see [Self::set_swap_interval].
+    #[doc(alias = "glfwSwapBuffers")]
+    pub fn swap_buffers(&self) {
+        self.window.swap_buffers()
+    }
+    /// Returns opengl function pointer for `proc_name`.
+    ///
+    /// See [Self::get_proc_addr_cstr] if you want to use a `&CStr` for `proc_name`.
+    /// This function just internally calls that function, after creating a `CString` out
+    /// of `proc_name`.
+    ///
+    /// # Panics
+    /// * if proc-name contains a null-byte (which is not allowed for C strings)
+    #[doc(alias = "glfwGetProcAddress")]
+    pub fn get_proc_addr(&self, proc_name: &str) -> *mut c_void {
+        let proc_name = CString::new(proc_name).expect("proc_name contains null-byte");
+        self.get_proc_addr_cstr(&proc_name)
+    }
+    /// Returns opengl function pointer for `proc_name`.
+    ///
+    /// If you have a `&str` for `proc_name`, use [Self::get_proc_addr] instead.
+    #[doc(alias = "glfwGetProcAddress")]
+    pub fn get_proc_addr_cstr(&self, proc_name: &CStr) -> *mut c_void {
+        self.window.get_proc_addr_cstr(proc_name)
+    }
+    /// This function sets the swap interval for the OpenGL or OpenGL ES context,
+    /// i.e. the number of screen updates to wait from the time
+    /// [Self::swap_buffers] was called before swapping the buffers
+    /// and returning. This is sometimes called vertical synchronization,
+    /// vertical retrace synchronization or just vsync.
+    ///
+    /// A context that supports either of the WGL_EXT_swap_control_tear and
+    /// GLX_EXT_swap_control_tear extensions also accepts negative swap intervals,
+    /// which allows the driver to swap immediately even if a frame arrives
+    /// a little bit late. [SwapInterval::Adaptive] uses that if the extensions are
+    /// supported, and falls back to [SwapInterval::Vsync] otherwise.
+    ///
+    /// Returns the interval that was actually set (after the fallback).
+    /// The same value is returned by [WindowProxy::swap_interval] later.
+    ///
+    /// This function does not apply to Vulkan. If you are rendering with Vulkan, see the present mode of your swapchain instead.
+    #[doc(alias = "glfwSwapInterval")]
+    pub fn set_swap_interval(&self, interval: impl Into<SwapInterval>) -> SwapInterval {
+        self.window.set_swap_interval(interval)
+    }
+}
+impl Drop for CurrentGuard<'_> {
+    fn drop(&mut self) {
+        let previous = CURRENT_GUARDS.with(|guards| {
+            let mut guards = guards.borrow_mut();
+            let index = guards.iter().position(|entry| entry.id == self.id)?;
+            let entry = guards.remove(index);
+            match guards.get_mut(index) {
+                // dropped before an inner guard, which restores our previous context instead.
+                Some(inner) => {
+                    inner.previous = entry.previous;
+                    None
+                }
+                None => Some(entry.previous),
+            }
+        });
+        let Some(previous) = previous else {
+            return;
+        };
+        // making the previous context current fails if it was destroyed or made current on
+        // another thread since the guard was created. Then, we just leave no context current.
+        let restored = previous.is_some_and(|previous| {
+            LOCAL_GL_CONTEXT
+                .with(|ctx| ctx.try_make_current(previous))
+                .is_ok()
+        });
+        if !restored {
+            self.window.make_uncurrent();
+        }
+    }
+}
+impl WindowProxy {
+    /// Makes the window current on the calling thread and returns a guard, which restores
+    /// the previously current context (or makes this window uncurrent) when dropped.
+    ///
+    /// This avoids forgetting to call [Self::make_uncurrent] before sending the window
+    /// to another thread or dropping it. Guards can be nested, see [CurrentGuard] for
+    /// what is restored when they are dropped out of order.
+    ///
+    /// # Panics
+    /// Same as [Self::make_current]
+    ///
+    /// ```no_run
+    /// # fn frame(window: &glfw_rust::WindowProxy) {
+    /// let current = window.make_current_scoped();
+    /// // load functions, draw etc..
+    /// current.swap_buffers();
+    /// // current is dropped here and the previous context is current again.
+    /// # }
+    /// ```
+    pub fn make_current_scoped(&self) -> CurrentGuard<'_> {
+        let previous = LOCAL_GL_CONTEXT.with(|ctx| ctx.get_current_data());
+        self.make_current();
+        let id = NEXT_CURRENT_GUARD_ID.fetch_add(1, Ordering::Relaxed);
+        CURRENT_GUARDS.with(|guards| guards.borrow_mut().push(CurrentGuardEntry { id, previous }));
+        CurrentGuard {
+            window: self,
+            id,
+            _not_send: std::marker::PhantomData,
+        }
+    }
+}
+
+/// A hidden window whose context shares objects (textures, buffers etc..) with the window
+/// that created it. Create it with [Window::new_shared_context].
+///
+/// This is meant to be sent to a worker (loader) thread and made current there
+/// (eg: with [WindowProxy::make_current_scoped]) to upload resources in the background.
+///
+/// The hidden window can only be destroyed on the main thread, so it is kept alive by the
+/// [EventLoop] and destroyed after this object is dropped:
+/// * immediately, if this is dropped on the main thread.
+/// * otherwise, on the next [EventLoop::poll_events] (or wait) or [Window::new_shared_context].
+///
+/// The hidden window is never destroyed while it is current on some thread, so it is safe to
+/// drop the parent window while a worker is still using this. gl objects stay alive as long as
+/// any context of the share group is alive. It is destroyed regardless when the [EventLoop] is
+/// dropped, so check [Self::is_share_group_alive] on long-running workers.
+///
+/// Dropping this object makes it uncurrent if it is current on the dropping thread.
+#[derive(Debug)]
+pub struct SharedContext {
+    window: WindowProxy,
+    /// The event loop holds a [std::sync::Weak] of this, to know when we were dropped.
+    token: Option<Arc<()>>,
+}
+impl Deref for SharedContext {
+    type Target = WindowProxy;
+
+    fn deref(&self) -> &Self::Target {
+        &self.window
+    }
+}
+impl Drop for SharedContext {
+    fn drop(&mut self) {
+        if self.window.is_current_on_current_thread() {
+            self.window.make_uncurrent();
+        }
+        self.token.take();
+        // only the main thread can destroy windows, the others leave it to the next poll.
+        let on_main_thread = MAIN_THREAD_LOCAL_DATA
+            .try_with(|data| data.is_alive.get())
+            .unwrap_or(false);
+        if on_main_thread {
+            destroy_released_windows();
+        }
+    }
+}
+impl SharedContext {
+    /// Returns false if the hidden window was destroyed (because the [EventLoop] was dropped).
+    pub fn is_share_group_alive(&self) -> bool {
+        self.window.data.is_alive.load(Ordering::Acquire)
+    }
+}
+/// Destroys the hidden windows of dropped [SharedContext]s and the dropped [Window]s, whose
+/// destruction was deferred (see [ThreadLocalEventLoopData::deferred_windows]).
+///
+/// Windows that are still current on some thread are kept for a later call, as destroying
+/// a context that is current on another thread is UB.
+///
+/// Must be called on the main thread.
+pub(crate) fn destroy_released_windows() {
+    let released: Vec<Arc<WindowData>> = MAIN_THREAD_LOCAL_DATA.with(|data| {
+        let mut windows = data.deferred_windows.borrow_mut();
+        let (released, in_use): (Vec<_>, Vec<_>) = std::mem::take(&mut *windows)
+            .into_iter()
+            .partition(|(_, token)| token.strong_count() == 0);
+        *windows = in_use;
+        released.into_iter().map(|(window, _)| window).collect()
+    });
+    let mut deferred = vec![];
+    for window in released {
+        // hold the lock, so that no thread can make it current while we destroy it.
+        let _guard = window
+            .current_thread
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if window.is_current.load(Ordering::Acquire) {
+            deferred.push((window.clone(), std::sync::Weak::new()));
+            continue;
+        }
+        window.is_alive.store(false, Ordering::Release);
+        unsafe { glfwDestroyWindow(window.window) };
+    }
+    MAIN_THREAD_LOCAL_DATA.with(|data| data.deferred_windows.borrow_mut().extend(deferred));
+}
+impl Window {
+    /// Creates an invisible 1x1 window, whose context shares objects with this window.
+    ///
+    /// The context uses the same client api, context creation api, version and profile as
+    /// this window, as contexts need to be compatible to share objects.
+    ///
+    /// Returns [ErrorCode::NoWindowContext] if this window doesn't have a gl context.
+    #[doc(alias = "glfwCreateWindow")]
+    pub fn new_shared_context(&self) -> GlfwResult<SharedContext> {
+        if !self.is_gl_window() {
+            return Err(GlfwError {
+                code: ErrorCode::NoWindowContext,
+                description: "glfw-rust: can't share context of a window without a gl context"
+                    .to_string(),
+                context: None,
+            });
+        }
+        destroy_released_windows();
+        let attributes = self.attributes()?;
+        let config = WindowConfig {
+            visible: Some(false),
+            focused: Some(false),
+            focus_on_show: Some(false),
+            decorated: Some(false),
+            client_api: Some(attributes.client_api),
+            context_creation_api: attributes.context_creation_api,
+            context_version_major: Some(attributes.context_version_major),
+            context_version_minor: Some(attributes.context_version_minor),
+            opengl_forward_compat: Some(attributes.opengl_forward_compat),
+            opengl_context_debug: Some(attributes.context_debug),
+            opengl_profile: attributes.opengl_profile,
+            context_robustness: attributes.context_robustness,
+            ..Default::default()
+        };
+        config.set_hints(&self.el)?;
+        // no callbacks are set, as the hidden window has no events worth reporting.
+        let window = self.el.checked(|| unsafe {
+            glfwCreateWindow(
+                1,
+                1,
+                c"glfw-rust shared context".as_ptr(),
+                std::ptr::null_mut(),
+                self.id().get_ptr(),
+            )
+        })?;
+        assert!(!window.is_null());
+        let data = match unsafe { WindowData::try_from_window(window, &self.el) } {
+            Ok(data) => Arc::new(data),
+            Err(error) => {
+                unsafe { glfwDestroyWindow(window) };
+                return Err(error);
+            }
+        };
+        let token = Arc::new(());
+        MAIN_THREAD_LOCAL_DATA.with(|main| {
+            main.deferred_windows
+                .borrow_mut()
+                .push((data.clone(), Arc::downgrade(&token)));
+        });
+        Ok(SharedContext {
+            window: WindowProxy::from_data(data, self.el.new_proxy()),
+            token: Some(token),
+        })
+    }
+}
+
+/// Loads gl functions of a current context and caches them by name.
+///
+/// Create it with [CurrentGuard::loader], which guarantees that the context is current
+/// on this thread for the lifetime of the loader.
+///
+/// Use [Self::loader_fn] or [Self::loader_fn_cstr] with gl bindings crates:
+/// ```no_run
+/// # fn load(window: &glfw_rust::WindowProxy) {
+/// let current = window.make_current_scoped();
+/// let loader = current.loader();
+/// // glow
+/// // let gl = unsafe { glow::Context::from_loader_function(loader.loader_fn()) };
+/// // gl crate
+/// // gl::load_with(loader.loader_fn());
+/// loader
+///     .require(&["glClear", "glDrawArrays"])
+///     .expect("missing gl functions");
+/// # }
+/// ```
+///
+/// Some implementations (eg: GLX) return non-null pointers even for functions that the context
+/// doesn't support. So, a symbol not being missing doesn't guarantee that it is usable.
+/// Check the version/extensions with [WindowProxy::context_info] for that.
+pub struct GlLoader<'a> {
+    guard: &'a CurrentGuard<'a>,
+    /// resolved symbols by name. null for missing symbols.
+    cache: RefCell<HashMap<String, *const c_void>>,
+}
+impl std::fmt::Debug for GlLoader<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GlLoader")
+            .field("window", &self.guard.id())
+            .field("cached", &self.cache.borrow().len())
+            .finish()
+    }
+}
+impl CurrentGuard<'_> {
+    /// Returns a [GlLoader] for the current context.
+    pub fn loader(&self) -> GlLoader<'_> {
+        GlLoader {
+            guard: self,
+            cache: Default::default(),
+        }
+    }
+}
+impl GlLoader<'_> {
+    /// Returns the function pointer for `name`, or null if it is missing.
+    ///
+    /// The result is cached, so later calls with the same name don't call into glfw.
+    ///
+    /// # Panics
+    /// * if `name` contains a null-byte
+    #[doc(alias = "glfwGetProcAddress")]
+    pub fn get(&self, name: &str) -> *const c_void {
+        if let Some(&ptr) = self.cache.borrow().get(name) {
+            return ptr;
+        }
+        let ptr = self.guard.get_proc_addr(name).cast_const();
+        self.cache.borrow_mut().insert(name.to_string(), ptr);
+        ptr
+    }
+    /// Same as [Self::get], but for `&CStr` names.
+    ///
+    /// # Panics
+    /// * if `name` is not valid utf-8 (gl function names are always ascii)
+    pub fn get_cstr(&self, name: &CStr) -> *const c_void {
+        let name_str = name.to_str().expect("gl function name is not utf-8");
+        if let Some(&ptr) = self.cache.borrow().get(name_str) {
+            return ptr;
+        }
+        let ptr = self.guard.get_proc_addr_cstr(name).cast_const();
+        self.cache.borrow_mut().insert(name_str.to_string(), ptr);
+        ptr
+    }
+    /// A closure compatible with `glow::Context::from_loader_function` and `gl::load_with`.
+    pub fn loader_fn(&self) -> impl FnMut(&str) -> *const c_void + '_ {
+        |name| self.get(name)
+    }
+    /// A closure compatible with `glow::Context::from_loader_function_cstr`.
+    pub fn loader_fn_cstr(&self) -> impl FnMut(&CStr) -> *const c_void + '_ {
+        |name| self.get_cstr(name)
+    }
+    /// Returns the names of all the symbols looked up so far, that were missing (null). Sorted by name.
+    ///
+    /// Useful after loading with a bindings crate, to report what the context is lacking.
+    pub fn missing_symbols(&self) -> Vec<String> {
+        let mut missing: Vec<String> = self
+            .cache
+            .borrow()
+            .iter()
+            .filter(|(_, ptr)| ptr.is_null())
+            .map(|(name, _)| name.clone())
+            .collect();
+        missing.sort();
+        missing
+    }
+    /// Looks up all the `names` and returns an error listing the missing ones, if any.
+    ///
+    /// Returns [ErrorCode::FeatureUnavailable] if any symbol is missing.
+    pub fn require(&self, names: &[&str]) -> GlfwResult<()> {
+        let missing: Vec<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| self.get(name).is_null())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(GlfwError {
+                code: ErrorCode::FeatureUnavailable,
+                description: format!(
+                    "glfw-rust: missing required gl functions: {}",
+                    missing.join(", ")
+                ),
+                context: None,
+            })
+        }
+    }
+}
+
+/// The depth buffer of an OSMesa window. see [WindowProxy::read_depth_buffer]
+#[cfg(feature = "osmesa")]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DepthBuffer {
+    pub width: u32,
+    pub height: u32,
+    /// The number of significant bytes per value (2 or 4). eg: 2 for a 16-bit depth buffer.
+    pub bytes_per_value: u32,
+    /// `width * height` depth values, in rows from top to bottom.
+    pub values: Vec<u32>,
+}
+#[cfg(feature = "osmesa")]
+impl WindowProxy {
+    /// Calls `glFinish` if the window is current on this thread, so that all the rendering
+    /// is done before we read the buffers.
+    fn finish_osmesa_rendering(&self) {
+        if self.is_current_on_current_thread() {
+            type GlFinish = unsafe extern "system" fn();
+            if let Some(finish) = unsafe { self.load_gl_fn::<GlFinish>(c"glFinish") } {
+                unsafe { finish() };
+            }
+        }
+    }
+    /// Returns [ErrorCode::NoWindowContext] if the window wasn't created with [ContextCreationApi::Osmesa]
+    fn check_osmesa(&self) -> GlfwResult<()> {
+        if self.data.context_creation_api == Some(ContextCreationApi::Osmesa) {
+            Ok(())
+        } else {
+            Err(GlfwError {
+                code: ErrorCode::NoWindowContext,
+                description: "glfw-rust: window was not created with the OSMesa context api"
+                    .to_string(),
+                context: None,
+            })
+        }
+    }
+    /// Copies the color buffer of a window created with [ContextCreationApi::Osmesa].
+    ///
+    /// This allows headless rendering (eg: golden image tests on CI without a gpu).
+    /// Create the window with [Platform::Null] (or an invisible window) and the OSMesa
+    /// context api, render and call this to get the result.
+    ///
+    /// If the window is current on this thread, we call `glFinish` before reading.
+    /// Otherwise, make sure that rendering is finished on the thread where it is current.
+    ///
+    /// OSMesa stores the rows bottom to top (like `glReadPixels`), but we flip them, so
+    /// the returned [Image] is top to bottom.
+    ///
+    /// Returns [ErrorCode::NoWindowContext] if the window doesn't use an OSMesa context.
+    #[doc(alias = "glfwGetOSMesaColorBuffer")]
+    pub fn read_color_buffer(&self) -> GlfwResult<Image> {
+        /// OSMesa pixel formats. glfw always creates `OSMESA_RGBA` contexts, but lets be safe.
+        const OSMESA_RGBA: i32 = 0x1908;
+        const OSMESA_BGRA: i32 = 0x1;
+        const OSMESA_ARGB: i32 = 0x2;
+        const OSMESA_RGB: i32 = 0x1907;
+        const OSMESA_BGR: i32 = 0x4;
+        self.check_osmesa()?;
+        self.finish_osmesa_rendering();
+        // the buffer is owned by the window, so copy it while holding the lock of `with_checked`.
+        // Otherwise, the window could be destroyed by another thread during the copy.
+        self.with_checked(|| {
+            let (mut width, mut height, mut format) = (0, 0, 0);
+            let mut buffer = std::ptr::null_mut();
+            clear_error();
+            unsafe {
+                glfwGetOSMesaColorBuffer(
+                    self.data.window,
+                    &mut width,
+                    &mut height,
+                    &mut format,
+                    &mut buffer,
+                );
+            }
+            get_error()?;
+            if buffer.is_null() || width <= 0 || height <= 0 {
+                return Ok(Image::default());
+            }
+            // red, green, blue and alpha offsets within a pixel + bytes per pixel
+            let (r, g, b, a, bpp) = match format {
+                OSMESA_RGBA => (0, 1, 2, Some(3), 4),
+                OSMESA_BGRA => (2, 1, 0, Some(3), 4),
+                OSMESA_ARGB => (1, 2, 3, Some(0), 4),
+                OSMESA_RGB => (0, 1, 2, None, 3),
+                OSMESA_BGR => (2, 1, 0, None, 3),
+                _ => {
+                    return Err(GlfwError {
+                        code: ErrorCode::FormatUnavailable,
+                        description: format!(
+                            "glfw-rust: unsupported OSMesa color format {format:#x}"
+                        ),
+                        context: None,
+                    })
+                }
+            };
+            let (width, height) = (width as usize, height as usize);
+            let src =
+                unsafe { std::slice::from_raw_parts(buffer.cast::<u8>(), width * height * bpp) };
+            let mut pixels = Vec::with_capacity(width * height * 4);
+            for row in src.chunks_exact(width * bpp).rev() {
+                for pixel in row.chunks_exact(bpp) {
+                    pixels.extend_from_slice(&[
+                        pixel[r],
+                        pixel[g],
+                        pixel[b],
+                        a.map(|a| pixel[a]).unwrap_or(u8::MAX),
+                    ]);
+                }
+            }
+            Ok(Image {
+                width: width as u32,
+                height: height as u32,
+                pixels,
+            })
+        })
+    }
+    /// Copies the depth buffer of a window created with [ContextCreationApi::Osmesa].
+    ///
+    /// Like [Self::read_color_buffer], the rows are flipped to be top to bottom.
+    ///
+    /// Returns [ErrorCode::NoWindowContext] if the window doesn't use an OSMesa context.
+    #[doc(alias = "glfwGetOSMesaDepthBuffer")]
+    pub fn read_depth_buffer(&self) -> GlfwResult<DepthBuffer> {
+        self.check_osmesa()?;
+        self.finish_osmesa_rendering();
+        // see read_color_buffer for why we copy inside `with_checked`.
+        self.with_checked(|| {
+            let (mut width, mut height, mut bytes_per_value) = (0, 0, 0);
+            let mut buffer = std::ptr::null_mut();
+            clear_error();
+            unsafe {
+                glfwGetOSMesaDepthBuffer(
+                    self.data.window,
+                    &mut width,
+                    &mut height,
+                    &mut bytes_per_value,
+                    &mut buffer,
+                );
+            }
+            get_error()?;
+            if buffer.is_null() || width <= 0 || height <= 0 {
+                return Ok(DepthBuffer::default());
+            }
+            let (width, height) = (width as usize, height as usize);
+            // OSMesa stores depth values as GLushort for 16-bit buffers and GLuint otherwise.
+            let values: Vec<u32> = if bytes_per_value == 2 {
+                let src =
+                    unsafe { std::slice::from_raw_parts(buffer.cast::<u16>(), width * height) };
+                src.chunks_exact(width)
+                    .rev()
+                    .flatten()
+                    .map(|&v| v as u32)
+                    .collect()
+            } else {
+                let src =
+                    unsafe { std::slice::from_raw_parts(buffer.cast::<u32>(), width * height) };
+                src.chunks_exact(width).rev().flatten().copied().collect()
+            };
+            Ok(DepthBuffer {
+                width: width as u32,
+                height: height as u32,
+                bytes_per_value: bytes_per_value as u32,
+                values,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn parse_version_strings() {
+        assert_eq!(
+            parse_gl_version("4.5 (Core Profile) Mesa 23.0.4"),
+            (false, 4, 5)
+        );
+        assert_eq!(parse_gl_version("3.1.0 NVIDIA 535.54"), (false, 3, 1));
+        assert_eq!(parse_gl_version("OpenGL ES 3.2 Mesa 23.0.4"), (true, 3, 2));
+        assert_eq!(parse_gl_version("OpenGL ES-CM 1.1 Mesa"), (true, 1, 1));
+        assert_eq!(parse_gl_version(""), (false, 0, 0));
+    }
+    #[cfg(all(feature = "testing", feature = "osmesa"))]
+    #[test]
+    fn replaced_debug_filters_stay_alive() {
+        const GL_DEBUG_SOURCE_APPLICATION: u32 = 0x824A;
+        const GL_DEBUG_TYPE_MARKER: u32 = 0x8268;
+        const GL_DEBUG_SEVERITY_HIGH: u32 = 0x9146;
+        type GlDebugMessageInsert =
+            unsafe extern "system" fn(u32, u32, u32, u32, i32, *const std::ffi::c_char);
+
+        let el = crate::testing::NullEventLoop::new().unwrap();
+        let Some(window) = el.osmesa_window(WindowConfig {
+            opengl_context_debug: Some(true),
+            ..Default::default()
+        }) else {
+            return;
+        };
+        let guard = window.make_current_scoped();
+        let asynchronous = GlDebugFilter {
+            synchronous: false,
+            ..Default::default()
+        };
+        match window.enable_gl_debug_output(asynchronous.clone()) {
+            Ok(()) => {}
+            Err(error) if error.code == ErrorCode::FeatureUnavailable => {
+                eprintln!("skipping debug output test: {error}");
+                return;
+            }
+            Err(error) => panic!("failed to enable debug output: {error}"),
+        }
+        let first = Arc::as_ptr(&window.data.gl_debug_filters.lock().unwrap()[0]);
+        window
+            .enable_gl_debug_output(GlDebugFilter::default())
+            .unwrap();
+        {
+            let filters = window.data.gl_debug_filters.lock().unwrap();
+            assert_eq!(filters.len(), 2);
+            // the driver may still hold the old pointer, so it must not move or be freed.
+            assert_eq!(Arc::as_ptr(&filters[0]), first);
+            assert_eq!(*filters[0], asynchronous);
+        }
+        unsafe {
+            let insert: GlDebugMessageInsert = window
+                .load_gl_fn(c"glDebugMessageInsert")
+                .expect("glDebugMessageInsert");
+            insert(
+                GL_DEBUG_SOURCE_APPLICATION,
+                GL_DEBUG_TYPE_MARKER,
+                1,
+                GL_DEBUG_SEVERITY_HIGH,
+                -1,
+                c"glfw-rust debug output test".as_ptr(),
+            );
+        }
+        drop(guard);
+    }
+}
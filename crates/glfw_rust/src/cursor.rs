@@ -1,11 +1,13 @@
+use std::sync::atomic::AtomicU64;
+
 use super::ffi::*;
 use super::*;
 
 /// A custom cursor to use for your window.
-/// 
+///
 /// You can use one of the provided [StdCursor]s or create one from pixels
 /// using [Cursor::new_from_pixels].
-/// 
+///
 /// Once you have the cursor, you just need to set it with [Window::set_cursor].
 #[derive(Debug)]
 pub struct Cursor {
@@ -35,20 +37,55 @@ impl Cursor {
         y_hot: i32,
     ) -> Option<Self> {
         assert!(width as usize * height as usize * 4 == pixels.len());
+        Self::try_new_from_pixels(el, width, height, pixels, x_hot, y_hot).ok()
+    }
+    /// Like [Self::new_from_pixels], but returns an error instead of panicking or returning `None`.
+    ///
+    /// Returns [CrateError::InvalidPixelBuffer] if the length of `pixels` is not
+    /// `width * height * 4` or if the dimensions don't fit in an `i32`.
+    #[doc(alias = "glfwCreateCursor")]
+    pub fn try_new_from_pixels(
+        el: Rc<EventLoop>,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        x_hot: i32,
+        y_hot: i32,
+    ) -> GlfwResult<Self> {
+        let invalid = |description: String| GlfwError {
+            code: ErrorCode::Crate(CrateError::InvalidPixelBuffer),
+            description,
+            context: None,
+        };
+        if width as usize * height as usize * 4 != pixels.len() {
+            return Err(invalid(format!(
+                "glfw-rust: cursor of {width}x{height} needs {} bytes, but got {}",
+                width as usize * height as usize * 4,
+                pixels.len()
+            )));
+        }
+        let (Ok(image_width), Ok(image_height)) = (width.try_into(), height.try_into()) else {
+            return Err(invalid(format!(
+                "glfw-rust: cursor of {width}x{height} is too large"
+            )));
+        };
         let image = GLFWimage {
-            width: width.try_into().unwrap(),
-            height: height.try_into().unwrap(),
+            width: image_width,
+            height: image_height,
             pixels: pixels.as_ptr().cast_mut(),
         };
-        let cursor = unsafe { glfwCreateCursor(&image, x_hot, y_hot) };
+        let cursor = el.checked(|| unsafe { glfwCreateCursor(&image, x_hot, y_hot) })?;
         if cursor.is_null() {
-            None
-        } else {
-            Some(Cursor {
-                ptr: cursor,
-                _el: el,
-            })
+            return Err(GlfwError {
+                code: ErrorCode::PlatformError,
+                description: "glfw-rust: failed to create cursor".to_string(),
+                context: None,
+            });
         }
+        Ok(Cursor {
+            ptr: cursor,
+            _el: el,
+        })
     }
     /**
     Returns a cursor with a standard shape, that can be set for a window
@@ -58,7 +95,7 @@ impl Cursor {
     Most of these shapes are guaranteed to exist on every supported platform
     but a few may not be present. See the table below for details.
 
-    |Cursor shape	           | Windows |  macOS  |  X11  |  Wayland  |
+    |Cursor shape               | Windows |  macOS  |  X11  |  Wayland  |
     | -----------------------  | ------- | ------- | ----- | --------- |
     | [StdCursor::Arrow]       | Yes     | Yes     | Yes   | Yes       |
     | [StdCursor::Ibeam]       | Yes     | Yes     | Yes   | Yes       |
@@ -88,7 +125,7 @@ impl Cursor {
             })
         }
     }
-    /// Just provides the inner pointer. 
+    /// Just provides the inner pointer.
     pub fn get_ptr(&self) -> *mut GLFWcursor {
         self.ptr
     }
@@ -100,3 +137,367 @@ impl Drop for Cursor {
         }
     }
 }
+
+/// A cursor made of frames, each shown for its own duration. Useful for busy/spinner cursors.
+///
+/// Set it with [Window::set_animated_cursor]. The event loop switches the frames of all the
+/// windows using it, from inside [EventLoop::poll_events] and related methods.
+/// [EventLoop::wait_events] and [EventLoop::wait_events_timeout] wake up when the next frame is due,
+/// so an idle app still animates the cursor without busy-looping.
+#[derive(Debug)]
+pub struct AnimatedCursor {
+    frames: Vec<(Cursor, f64)>,
+}
+impl AnimatedCursor {
+    /// Creates an animated cursor from a list of frames and how long (in seconds) each frame is shown.
+    ///
+    /// # Panics
+    /// * if `frames` is empty
+    /// * if any of the durations is not positive
+    pub fn new(frames: Vec<(Cursor, f64)>) -> Self {
+        Self::try_new(frames).expect("invalid animated cursor")
+    }
+    /// Like [Self::new], but returns [ErrorCode::InvalidValue] instead of panicking.
+    pub fn try_new(frames: Vec<(Cursor, f64)>) -> GlfwResult<Self> {
+        let invalid = |description: &str| GlfwError {
+            code: ErrorCode::InvalidValue,
+            description: description.to_string(),
+            context: None,
+        };
+        if frames.is_empty() {
+            return Err(invalid("glfw-rust: animated cursor has no frames"));
+        }
+        // also rejects NaN
+        if !frames.iter().all(|(_, duration)| *duration > 0.0) {
+            return Err(invalid(
+                "glfw-rust: animated cursor frame durations must be positive",
+            ));
+        }
+        Ok(Self { frames })
+    }
+    /// The frames and their durations (in seconds).
+    pub fn frames(&self) -> &[(Cursor, f64)] {
+        &self.frames
+    }
+    /// The duration of one loop of the animation, in seconds.
+    pub fn total_duration(&self) -> f64 {
+        self.frames.iter().map(|(_, duration)| duration).sum()
+    }
+    /// The frame shown `elapsed` seconds after the animation started, and the seconds
+    /// until the next frame.
+    fn frame_at(&self, elapsed: f64) -> (usize, f64) {
+        frame_at(self.frames.iter().map(|(_, duration)| *duration), elapsed)
+    }
+}
+/// [AnimatedCursor::frame_at] for a list of frame durations.
+///
+/// This wraps `elapsed` around the total duration, so that frames missed while the app was
+/// busy (or asleep) are skipped in one step, instead of one frame at a time.
+fn frame_at(durations: impl Iterator<Item = f64> + Clone, elapsed: f64) -> (usize, f64) {
+    let total: f64 = durations.clone().sum();
+    let mut time = elapsed.max(0.0) % total;
+    for (index, duration) in durations.clone().enumerate() {
+        if time < duration {
+            return (index, duration - time);
+        }
+        time -= duration;
+    }
+    // only reachable through rounding errors at the very end of the loop.
+    (0, durations.take(1).sum())
+}
+/// A window that uses an [AnimatedCursor]. Stored in [ThreadLocalEventLoopData::animated_cursors].
+#[derive(Debug)]
+pub(crate) struct AnimatedCursorState {
+    /// The window using the cursor
+    pub window: WindowId,
+    /// The cursor
+    pub cursor: Rc<AnimatedCursor>,
+    /// The frame that is currently set
+    pub frame: usize,
+    /// The time (from [EventLoopProxy::get_time]) at which the animation started.
+    pub started_at: f64,
+    /// The time (from [EventLoopProxy::get_time]) at which we need to switch to the next frame.
+    pub next_frame_at: f64,
+}
+impl Window {
+    /// Sets an [AnimatedCursor] for the window, starting with the first frame.
+    ///
+    /// Setting a static cursor with [Window::set_cursor] stops the animation.
+    #[doc(alias = "glfwSetCursor")]
+    pub fn set_animated_cursor(&self, cursor: Rc<AnimatedCursor>) {
+        let window = self.id();
+        let now = self.get_time();
+        let (first, duration) = &cursor.frames[0];
+        unsafe { glfwSetCursor(window.get_ptr(), first.get_ptr()) };
+        let next_frame_at = now + duration;
+        self.std_cursor.take();
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            let mut animated = data.animated_cursors.borrow_mut();
+            animated.retain(|state| state.window != window);
+            animated.push(AnimatedCursorState {
+                window,
+                cursor,
+                frame: 0,
+                started_at: now,
+                next_frame_at,
+            });
+        });
+    }
+    /// Stops animating the cursor of this window (if any). The current frame stays set.
+    pub(crate) fn stop_cursor_animation(&self) {
+        let window = self.id();
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            data.animated_cursors
+                .borrow_mut()
+                .retain(|state| state.window != window)
+        });
+    }
+}
+impl EventLoop {
+    /// Switches the frames of animated cursors that are due.
+    pub(crate) fn advance_animated_cursors(&self) {
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            let mut animated = data.animated_cursors.borrow_mut();
+            if animated.is_empty() {
+                return;
+            }
+            let now = unsafe { glfwGetTime() };
+            for state in animated.iter_mut() {
+                if state.next_frame_at > now {
+                    continue;
+                }
+                // if we are late (eg: the app was busy), this skips the frames we missed.
+                let (frame, remaining) = state.cursor.frame_at(now - state.started_at);
+                state.next_frame_at = now + remaining;
+                if frame != state.frame {
+                    state.frame = frame;
+                    let cursor = &state.cursor.frames[frame].0;
+                    unsafe { glfwSetCursor(state.window.get_ptr(), cursor.get_ptr()) };
+                }
+            }
+        });
+    }
+    /// The number of seconds until the next animated cursor frame is due, if any cursor is animated.
+    pub(crate) fn next_animated_cursor_timeout(&self) -> Option<f64> {
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            let animated = data.animated_cursors.borrow();
+            let next = animated
+                .iter()
+                .map(|state| state.next_frame_at)
+                .min_by(f64::total_cmp)?;
+            Some((next - unsafe { glfwGetTime() }).max(0.0))
+        })
+    }
+}
+
+impl StdCursor {
+    /// The shape to use if this one is not available in the cursor theme.
+    ///
+    /// Diagonal resize cursors fall back to [StdCursor::ResizeAll] and everything
+    /// else to [StdCursor::Arrow], which is available on all platforms.
+    fn fallback(self) -> Option<StdCursor> {
+        match self {
+            StdCursor::Arrow => None,
+            StdCursor::ResizeNESW | StdCursor::ResizeNWSE => Some(StdCursor::ResizeAll),
+            _ => Some(StdCursor::Arrow),
+        }
+    }
+}
+impl EventLoop {
+    /// Returns a standard cursor, creating it only if no one is using one of that shape already.
+    ///
+    /// Unlike [Cursor::new_std_cursor], this falls back to a similar shape if the cursor theme
+    /// doesn't have the requested one ([ErrorCode::CursorUnavailable]). eg: [StdCursor::NotAllowed]
+    /// falls back to [StdCursor::Arrow].
+    ///
+    /// The cache only holds weak references, so the cursor is destroyed once all the
+    /// [Rc]s (including windows using it via [Window::set_std_cursor]) are dropped.
+    pub fn std_cursor(self: &Rc<Self>, shape: StdCursor) -> GlfwResult<Rc<Cursor>> {
+        if let Some(cursor) = self
+            .std_cursors
+            .borrow()
+            .get(&shape)
+            .and_then(|c| c.upgrade())
+        {
+            return Ok(cursor);
+        }
+        clear_error();
+        let cursor = match Cursor::new_std_cursor(self.clone(), shape) {
+            Some(cursor) => Rc::new(cursor),
+            None => {
+                let error = get_error().err().unwrap_or_else(|| GlfwError {
+                    code: ErrorCode::CursorUnavailable,
+                    description: format!("glfw-rust: failed to create {shape:?} cursor"),
+                    context: None,
+                });
+                match shape.fallback() {
+                    Some(fallback) if error.code == ErrorCode::CursorUnavailable => {
+                        tracing::debug!("{shape:?} cursor is unavailable, using {fallback:?}");
+                        self.std_cursor(fallback)?
+                    }
+                    _ => return Err(error),
+                }
+            }
+        };
+        self.std_cursors
+            .borrow_mut()
+            .insert(shape, Rc::downgrade(&cursor));
+        Ok(cursor)
+    }
+}
+impl Window {
+    /// Sets a standard cursor for the window, using the cache of [EventLoop::std_cursor].
+    ///
+    /// The window keeps the cursor alive until another cursor is set.
+    #[doc(alias = "glfwSetCursor")]
+    pub fn set_std_cursor(&self, shape: StdCursor) -> GlfwResult<()> {
+        let cursor = self.el.std_cursor(shape)?;
+        self.set_cursor(Some(&cursor));
+        self.std_cursor.replace(Some(cursor));
+        Ok(())
+    }
+}
+
+/// The state of a window with an active [PointerLock]. Stored in [ThreadLocalEventLoopData::pointer_locks].
+#[derive(Debug)]
+pub(crate) struct PointerLockState {
+    /// Identifies the [PointerLock] that created this, so that a stale guard (whose lock was
+    /// released on focus loss) doesn't release a newer lock of the same window.
+    generation: u64,
+    previous_mode: CursorMode,
+    previous_raw_motion: bool,
+    previous_pos: (f64, f64),
+    /// The last (virtual) cursor position, used to compute [Event::MouseMotion].
+    pub(crate) last_pos: (f64, f64),
+}
+/// The generation of the next [PointerLock].
+static NEXT_POINTER_LOCK_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Restores the cursor state of the window from before it was locked.
+///
+/// Does nothing if the window doesn't have an active lock, or if `generation` is set
+/// and doesn't match the active lock. Called from [PointerLock::drop] and on focus loss.
+pub(crate) fn release_pointer_lock(window: WindowId, generation: Option<u64>) {
+    let Some(state) = MAIN_THREAD_LOCAL_DATA.with(|data| {
+        let mut locks = data.pointer_locks.borrow_mut();
+        let matches = locks.get(&window).is_some_and(|state| {
+            generation.is_none_or(|generation| state.generation == generation)
+        });
+        matches.then(|| locks.remove(&window)).flatten()
+    }) else {
+        return;
+    };
+    unsafe {
+        glfwSetInputMode(window.get_ptr(), GLFW_CURSOR, state.previous_mode as i32);
+        if glfwRawMouseMotionSupported() == GLFW_TRUE {
+            glfwSetInputMode(
+                window.get_ptr(),
+                GLFW_RAW_MOUSE_MOTION,
+                bool_to_glfw(state.previous_raw_motion),
+            );
+        }
+        // fails silently if the window lost focus
+        glfwSetCursorPos(window.get_ptr(), state.previous_pos.0, state.previous_pos.1);
+    }
+    log_error();
+}
+/// A guard that locks the pointer to a window, for things like mouselook in FPS cameras.
+///
+/// While active:
+/// 1. The cursor is [CursorMode::Disabled] (hidden and grabbed).
+/// 2. Raw mouse motion is enabled, if [supported](EventLoop::is_raw_mouse_motion_supported).
+/// 3. Every [Event::CursorPos] of the window is followed by an [Event::MouseMotion] with the delta.
+///
+/// The lock is released when this is dropped or when the window loses focus (whichever is first),
+/// restoring the previous cursor mode, raw motion setting and cursor position.
+/// Use [PointerLock::is_active] to check if the lock was released due to focus loss.
+#[must_use = "the pointer is unlocked when the guard is dropped"]
+pub struct PointerLock<'a> {
+    window: &'a Window,
+    generation: u64,
+}
+impl PointerLock<'_> {
+    /// Whether the lock is still active. Becomes false after the window loses focus.
+    pub fn is_active(&self) -> bool {
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            data.pointer_locks
+                .borrow()
+                .get(&self.window.id())
+                .is_some_and(|state| state.generation == self.generation)
+        })
+    }
+    /// The window whose pointer is locked.
+    pub fn window(&self) -> &Window {
+        self.window
+    }
+}
+impl Drop for PointerLock<'_> {
+    fn drop(&mut self) {
+        release_pointer_lock(self.window.id(), Some(self.generation));
+    }
+}
+impl Window {
+    /// Locks the pointer to this window. See [PointerLock].
+    ///
+    /// Returns [ErrorCode::InvalidValue] if the window already has an active lock.
+    pub fn lock_pointer(&self) -> GlfwResult<PointerLock<'_>> {
+        let id = self.id();
+        if MAIN_THREAD_LOCAL_DATA.with(|data| data.pointer_locks.borrow().contains_key(&id)) {
+            return Err(GlfwError {
+                code: ErrorCode::InvalidValue,
+                description: "glfw-rust: the pointer of this window is already locked".to_string(),
+                context: None,
+            });
+        }
+        let previous_mode = self.get_cursor_mode();
+        let previous_raw_motion = self.get_raw_mouse_motion();
+        let previous_pos = self.get_cursor_pos();
+        self.set_cursor_mode(CursorMode::Disabled);
+        if self.el.is_raw_mouse_motion_supported() {
+            self.set_raw_mouse_motion(true);
+        }
+        let last_pos = self.get_cursor_pos();
+        let generation = NEXT_POINTER_LOCK_GENERATION.fetch_add(1, Ordering::Relaxed);
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            data.pointer_locks.borrow_mut().insert(
+                id,
+                PointerLockState {
+                    generation,
+                    previous_mode,
+                    previous_raw_motion,
+                    previous_pos,
+                    last_pos,
+                },
+            )
+        });
+        Ok(PointerLock {
+            window: self,
+            generation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn frame(durations: &[f64], elapsed: f64) -> (usize, f64) {
+        let (index, remaining) = frame_at(durations.iter().copied(), elapsed);
+        // round away the floating point noise of the subtractions
+        (index, (remaining * 1e6).round() / 1e6)
+    }
+    #[test]
+    fn select_animation_frames() {
+        let durations = [0.1, 0.2, 0.3];
+        assert_eq!(frame(&durations, 0.0), (0, 0.1));
+        assert_eq!(frame(&durations, 0.15), (1, 0.15));
+        assert_eq!(frame(&durations, 0.35), (2, 0.25));
+        assert_eq!(frame(&durations, 0.59), (2, 0.01));
+        // wraps around
+        assert_eq!(frame(&durations, 0.65), (0, 0.05));
+        // a clock that went backwards just restarts the animation
+        assert_eq!(frame(&durations, -1.0), (0, 0.1));
+        // a huge delay (eg: after suspend) or tiny durations are a single step
+        assert_eq!(frame(&durations, 6e9 + 0.45).0, 2);
+        let (index, remaining) = frame_at([1e-9; 3].into_iter(), 1e6);
+        assert!(index < 3 && remaining > 0.0 && remaining <= 1e-9);
+    }
+}
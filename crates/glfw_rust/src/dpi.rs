@@ -0,0 +1,303 @@
+//! Glfw mixes two units in its API:
+//!
+//! * **screen coordinates**: used by window size/position, cursor position and monitor work area.
+//! * **pixels**: used by framebuffer size (eg: for `glViewport` or vulkan swapchain extent).
+//!
+//! On some platforms (Windows/X11), screen coordinates are the same as pixels. On others
+//! (macOS/Wayland), screen coordinates are scaled by the content scale of the monitor.
+//!
+//! To avoid mixing these up, this module provides typed sizes and positions:
+//!
+//! * **Physical** types are in pixels.
+//! * **Logical** types are physical values divided by the content scale ([Window::get_scale_factor]).
+//!   These are what you want for laying out UI, as they look the same size on all monitors.
+//!
+//! [Window] provides typed variants of its size/position getters and setters like
+//! [Window::get_logical_size] or [Window::set_physical_pos], which do the conversions for you.
+use crate::*;
+
+/// Size in logical units (physical pixels divided by scale factor).
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct LogicalSize {
+    pub width: f64,
+    pub height: f64,
+}
+/// Size in physical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PhysicalSize {
+    pub width: u32,
+    pub height: u32,
+}
+/// Position in logical units (physical pixels divided by scale factor).
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct LogicalPosition {
+    pub x: f64,
+    pub y: f64,
+}
+/// Position in physical pixels.
+///
+/// This uses `f64`, because cursor positions can have sub-pixel precision.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct PhysicalPosition {
+    pub x: f64,
+    pub y: f64,
+}
+impl LogicalSize {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+    /// Multiplies with `scale_factor` and rounds to the nearest pixel.
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalSize {
+        PhysicalSize {
+            width: (self.width * scale_factor).round() as u32,
+            height: (self.height * scale_factor).round() as u32,
+        }
+    }
+}
+impl PhysicalSize {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+    /// Divides by `scale_factor`.
+    pub fn to_logical(self, scale_factor: f64) -> LogicalSize {
+        LogicalSize {
+            width: self.width as f64 / scale_factor,
+            height: self.height as f64 / scale_factor,
+        }
+    }
+}
+impl LogicalPosition {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+    /// Multiplies with `scale_factor`.
+    pub fn to_physical(self, scale_factor: f64) -> PhysicalPosition {
+        PhysicalPosition {
+            x: self.x * scale_factor,
+            y: self.y * scale_factor,
+        }
+    }
+}
+impl PhysicalPosition {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+    /// Divides by `scale_factor`.
+    pub fn to_logical(self, scale_factor: f64) -> LogicalPosition {
+        LogicalPosition {
+            x: self.x / scale_factor,
+            y: self.y / scale_factor,
+        }
+    }
+}
+
+/// The content scale as a scale factor. Falls back to 1.0 for invalid (zero/negative) scales.
+fn scale_factor(content_scale: f32) -> f64 {
+    if content_scale > 0.0 {
+        content_scale as f64
+    } else {
+        1.0
+    }
+}
+/// framebuffer size / window size. Falls back to 1.0 if either size is zero (eg: iconified).
+fn pixels_per_screen_coordinate(
+    (width, height): (i32, i32),
+    (fb_width, fb_height): (u32, u32),
+) -> (f64, f64) {
+    if width <= 0 || height <= 0 || fb_width == 0 || fb_height == 0 {
+        return (1.0, 1.0);
+    }
+    (
+        fb_width as f64 / width as f64,
+        fb_height as f64 / height as f64,
+    )
+}
+impl Window {
+    /// The content scale of the window, used to convert between logical and physical units.
+    ///
+    /// Glfw provides separate x and y scales, but they are the same on all
+    /// the platforms we know of. So, we just use the x scale.
+    ///
+    /// see [Window::get_content_scale]
+    pub fn get_scale_factor(&self) -> f64 {
+        let (xscale, _) = self.get_content_scale();
+        scale_factor(xscale)
+    }
+    /// The number of pixels per screen coordinate (framebuffer size / window size).
+    ///
+    /// This is 1.0 on platforms where screen coordinates are pixels (Windows/X11).
+    ///
+    /// If the window is iconified, the sizes may be zero and we just return 1.0.
+    fn get_pixels_per_screen_coordinate(&self) -> (f64, f64) {
+        pixels_per_screen_coordinate(self.get_size(), self.get_framebuffer_size())
+    }
+    /// Converts a position in screen coordinates (eg: from [Event::CursorPos] or [Event::Pos])
+    /// to physical pixels.
+    pub fn screen_to_physical(&self, x: f64, y: f64) -> PhysicalPosition {
+        let (xratio, yratio) = self.get_pixels_per_screen_coordinate();
+        PhysicalPosition {
+            x: x * xratio,
+            y: y * yratio,
+        }
+    }
+    /// Converts a position in screen coordinates (eg: from [Event::CursorPos] or [Event::Pos])
+    /// to logical units.
+    pub fn screen_to_logical(&self, x: f64, y: f64) -> LogicalPosition {
+        self.screen_to_physical(x, y)
+            .to_logical(self.get_scale_factor())
+    }
+    /// Converts a position in physical pixels to screen coordinates.
+    fn physical_to_screen(&self, position: PhysicalPosition) -> (f64, f64) {
+        let (xratio, yratio) = self.get_pixels_per_screen_coordinate();
+        (position.x / xratio, position.y / yratio)
+    }
+    /// The size of the content area in physical pixels.
+    ///
+    /// This is the same as [Window::get_framebuffer_size].
+    #[doc(alias = "glfwGetFramebufferSize")]
+    pub fn get_physical_size(&self) -> PhysicalSize {
+        let (width, height) = self.get_framebuffer_size();
+        PhysicalSize { width, height }
+    }
+    /// The size of the content area in logical units.
+    pub fn get_logical_size(&self) -> LogicalSize {
+        self.get_physical_size().to_logical(self.get_scale_factor())
+    }
+    /// Resizes the content area to `size` physical pixels.
+    ///
+    /// see [Window::set_size]
+    #[doc(alias = "glfwSetWindowSize")]
+    pub fn set_physical_size(&self, size: PhysicalSize) {
+        let (width, height) = self.physical_to_screen(PhysicalPosition {
+            x: size.width as f64,
+            y: size.height as f64,
+        });
+        self.set_size(width.round() as u32, height.round() as u32);
+    }
+    /// Resizes the content area to `size` logical units.
+    ///
+    /// see [Window::set_size]
+    #[doc(alias = "glfwSetWindowSize")]
+    pub fn set_logical_size(&self, size: LogicalSize) {
+        self.set_physical_size(size.to_physical(self.get_scale_factor()));
+    }
+    /// The position of the upper-left corner of the content area in physical pixels.
+    ///
+    /// see [Window::get_pos]
+    #[doc(alias = "glfwGetWindowPos")]
+    pub fn get_physical_pos(&self) -> PhysicalPosition {
+        let (x, y) = self.get_pos();
+        self.screen_to_physical(x as f64, y as f64)
+    }
+    /// The position of the upper-left corner of the content area in logical units.
+    ///
+    /// see [Window::get_pos]
+    #[doc(alias = "glfwGetWindowPos")]
+    pub fn get_logical_pos(&self) -> LogicalPosition {
+        let (x, y) = self.get_pos();
+        self.screen_to_logical(x as f64, y as f64)
+    }
+    /// Moves the upper-left corner of the content area to `position` in physical pixels.
+    ///
+    /// see [Window::set_pos]
+    #[doc(alias = "glfwSetWindowPos")]
+    pub fn set_physical_pos(&self, position: PhysicalPosition) {
+        let (x, y) = self.physical_to_screen(position);
+        self.set_pos(x.round() as i32, y.round() as i32);
+    }
+    /// Moves the upper-left corner of the content area to `position` in logical units.
+    ///
+    /// see [Window::set_pos]
+    #[doc(alias = "glfwSetWindowPos")]
+    pub fn set_logical_pos(&self, position: LogicalPosition) {
+        self.set_physical_pos(position.to_physical(self.get_scale_factor()));
+    }
+    /// The cursor position relative to the content area in physical pixels.
+    ///
+    /// see [Window::get_cursor_pos]
+    #[doc(alias = "glfwGetCursorPos")]
+    pub fn get_physical_cursor_pos(&self) -> PhysicalPosition {
+        let (x, y) = self.get_cursor_pos();
+        self.screen_to_physical(x, y)
+    }
+    /// The cursor position relative to the content area in logical units.
+    ///
+    /// see [Window::get_cursor_pos]
+    #[doc(alias = "glfwGetCursorPos")]
+    pub fn get_logical_cursor_pos(&self) -> LogicalPosition {
+        let (x, y) = self.get_cursor_pos();
+        self.screen_to_logical(x, y)
+    }
+    /// Moves the cursor to `position` (in physical pixels) relative to the content area.
+    ///
+    /// see [Window::set_cursor_pos]
+    #[doc(alias = "glfwSetCursorPos")]
+    pub fn set_physical_cursor_pos(&self, position: PhysicalPosition) {
+        let (x, y) = self.physical_to_screen(position);
+        self.set_cursor_pos(x, y);
+    }
+    /// Moves the cursor to `position` (in logical units) relative to the content area.
+    ///
+    /// see [Window::set_cursor_pos]
+    #[doc(alias = "glfwSetCursorPos")]
+    pub fn set_logical_cursor_pos(&self, position: LogicalPosition) {
+        self.set_physical_cursor_pos(position.to_physical(self.get_scale_factor()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn logical_physical_round_trip() {
+        let logical = LogicalSize::new(800.0, 600.0);
+        let physical = logical.to_physical(1.5);
+        assert_eq!(physical, PhysicalSize::new(1200, 900));
+        assert_eq!(physical.to_logical(1.5), logical);
+
+        let position = PhysicalPosition::new(300.0, 150.0);
+        assert_eq!(position.to_logical(2.0), LogicalPosition::new(150.0, 75.0));
+        assert_eq!(position.to_logical(2.0).to_physical(2.0), position);
+    }
+    #[test]
+    fn zero_sizes_fall_back_to_one() {
+        // iconified windows report a zero framebuffer on some platforms
+        assert_eq!(pixels_per_screen_coordinate((800, 600), (0, 0)), (1.0, 1.0));
+        assert_eq!(
+            pixels_per_screen_coordinate((800, 600), (1600, 0)),
+            (1.0, 1.0)
+        );
+        assert_eq!(
+            pixels_per_screen_coordinate((0, 0), (1600, 1200)),
+            (1.0, 1.0)
+        );
+        assert_eq!(
+            pixels_per_screen_coordinate((800, 600), (1600, 1200)),
+            (2.0, 2.0)
+        );
+        assert_eq!(scale_factor(0.0), 1.0);
+        assert_eq!(scale_factor(-1.0), 1.0);
+    }
+    #[test]
+    fn non_integer_scale() {
+        assert_eq!(scale_factor(1.25), 1.25);
+        assert_eq!(
+            pixels_per_screen_coordinate((800, 600), (1000, 750)),
+            (1.25, 1.25)
+        );
+        // rounded to the nearest pixel
+        assert_eq!(
+            LogicalSize::new(101.0, 33.0).to_physical(1.25),
+            PhysicalSize::new(126, 41)
+        );
+        assert_eq!(
+            LogicalSize::new(101.0, 33.0).to_physical(1.5),
+            PhysicalSize::new(152, 50)
+        );
+        let logical = PhysicalSize::new(125, 75).to_logical(1.25);
+        assert_eq!(logical, LogicalSize::new(100.0, 60.0));
+        assert_eq!(logical.to_physical(1.25), PhysicalSize::new(125, 75));
+        let position = LogicalPosition::new(10.5, 3.0).to_physical(1.5);
+        assert_eq!(position, PhysicalPosition::new(15.75, 4.5));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+use std::panic::Location;
+use std::sync::PoisonError;
+use std::thread::ThreadId;
+use std::time::SystemTime;
+
+use crate::*;
+
+/// The errors recorded by the error callback, if [EventLoopConfig::error_history] is non-zero.
+///
+/// Errors from all threads are recorded here, as glfw calls the error callback on the
+/// thread where the error occurred.
+pub(crate) static ERROR_HISTORY: Mutex<Option<ErrorHistory>> = Mutex::new(None);
+
+/// A single error recorded in [ErrorHistory].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorRecord {
+    /// When the error occurred.
+    pub timestamp: SystemTime,
+    /// The thread on which the error occurred.
+    pub thread: ThreadId,
+    /// The name of [Self::thread], if it has one.
+    pub thread_name: Option<String>,
+    /// The error itself.
+    pub error: GlfwError,
+    /// Where the error was caught, if it was caught by [EventLoop::checked] or
+    /// [EventLoop::logged].
+    ///
+    /// Errors that are never checked (which is most of them) have no caller.
+    pub caller: Option<&'static Location<'static>>,
+}
+impl std::fmt::Display for ErrorRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let since_epoch = self
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "[{:.3}] thread {}",
+            since_epoch.as_secs_f64(),
+            self.thread_name.as_deref().unwrap_or("<unnamed>")
+        )?;
+        write!(f, " {}", self.error)?;
+        if let Some(caller) = self.caller {
+            write!(f, " (caught at {caller})")?;
+        }
+        Ok(())
+    }
+}
+
+/// A bounded ring buffer of the most recent glfw errors.
+///
+/// This is opt-in via [EventLoopConfig::error_history], and can be queried with
+/// [EventLoop::recent_errors]. It is meant for diagnostics, so that bug reports can
+/// include the last few errors:
+///
+/// ```rust
+/// # use glfw_rust::*;
+/// # fn report(el: &EventLoop) {
+/// eprintln!("recent glfw errors:\n{}", el.recent_errors());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorHistory {
+    capacity: usize,
+    records: VecDeque<ErrorRecord>,
+}
+impl ErrorHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+    /// Adds a record, evicting the oldest one if the history is full.
+    pub(crate) fn push(&mut self, record: ErrorRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+    /// Sets the caller of the most recent uncaught record of `error` on the current thread.
+    fn set_caller(&mut self, error: &GlfwError, caller: &'static Location<'static>) {
+        let thread = std::thread::current().id();
+        if let Some(record) = self.records.iter_mut().rev().find(|record| {
+            record.thread == thread
+                && record.caller.is_none()
+                && record.error.code == error.code
+                && record.error.description == error.description
+        }) {
+            record.caller = Some(caller);
+        }
+    }
+    /// The maximum number of records kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    /// Returns true if no errors were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    /// Iterates over the records, from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ErrorRecord> {
+        self.records.iter()
+    }
+}
+impl std::fmt::Display for ErrorHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for record in self.iter() {
+            writeln!(f, "{record}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the error in [ERROR_HISTORY], if enabled. Called from the error callback.
+pub(crate) fn record_error(error: &GlfwError) {
+    // a panic while the lock was held can't leave the history inconsistent, so keep recording.
+    let mut history = ERROR_HISTORY.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(history) = history.as_mut() {
+        let thread = std::thread::current();
+        history.push(ErrorRecord {
+            timestamp: SystemTime::now(),
+            thread: thread.id(),
+            thread_name: thread.name().map(str::to_string),
+            error: error.clone(),
+            caller: None,
+        });
+    }
+}
+/// Attaches the caller to the recorded error, once it is caught by [get_error].
+pub(crate) fn record_error_caller(error: &GlfwError, caller: &'static Location<'static>) {
+    let mut history = ERROR_HISTORY.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(history) = history.as_mut() {
+        history.set_caller(error, caller);
+    }
+}
+
+impl EventLoop {
+    /// Returns a snapshot of the most recent errors from all threads.
+    ///
+    /// This is always empty, unless [EventLoopConfig::error_history] was set. Errors are
+    /// recorded by the default error callback, so it is also empty if
+    /// [EventLoopConfig::error_callback] was set.
+    pub fn recent_errors(&self) -> ErrorHistory {
+        ERROR_HISTORY
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .unwrap_or_else(|| ErrorHistory::new(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(description: &str) -> ErrorRecord {
+        ErrorRecord {
+            timestamp: SystemTime::now(),
+            thread: std::thread::current().id(),
+            thread_name: None,
+            error: GlfwError {
+                code: ErrorCode::PlatformError,
+                description: description.to_string(),
+                context: None,
+            },
+            caller: None,
+        }
+    }
+
+    #[test]
+    fn ring_buffer() {
+        let mut history = ErrorHistory::new(2);
+        history.push(record("a"));
+        history.push(record("b"));
+        history.push(record("c"));
+        let descriptions: Vec<_> = history
+            .iter()
+            .map(|record| record.error.description.as_str())
+            .collect();
+        assert_eq!(descriptions, ["b", "c"]);
+
+        let caller = Location::caller();
+        history.set_caller(&record("b").error, caller);
+        assert_eq!(history.iter().next().unwrap().caller, Some(caller));
+        assert_eq!(history.iter().nth(1).unwrap().caller, None);
+
+        let mut disabled = ErrorHistory::new(0);
+        disabled.push(record("a"));
+        assert!(disabled.is_empty());
+    }
+}
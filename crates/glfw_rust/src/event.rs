@@ -1,7 +1,10 @@
+use std::path::PathBuf;
+
 use crate::*;
 /// <https://www.glfw.org/docs/latest/input_guide.html>
 ///
-///
+/// [Event::Error] is an error that occurred on the main thread. It is only emitted if
+/// [EventLoopConfig::error_events] is set.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Event {
     Error(GlfwError),
@@ -216,6 +219,16 @@ pub enum Event {
         x: f64,
         y: f64,
     },
+    /// relative cursor motion while a [PointerLock] is active on the window.
+    ///
+    /// This is the difference between consecutive [Event::CursorPos] events, and is
+    /// emitted right after them. If raw mouse motion is supported, the motion is unscaled
+    /// and unaccelerated.
+    MouseMotion {
+        window: WindowId,
+        dx: f64,
+        dy: f64,
+    },
     /// when the cursor enters or leaves the content area of a window
     ///
     /// You can query whether the cursor is currently inside the content area
@@ -233,10 +246,18 @@ pub enum Event {
         y: f64,
     },
     /// the paths of files and/or directories dropped on a window
+    ///
+    /// Use [DroppedPaths::classify] to split them into files and directories.
     Drop {
         window: WindowId,
+        /// The cursor position (in screen coordinates, relative to the content area) where
+        /// the paths were dropped. This is the position of the last [Event::CursorPos] of the window.
+        x: f64,
+        y: f64,
         /// The paths of the dropped files and/or directories.
-        paths: Vec<String>,
+        ///
+        /// On unix, these are built from the raw bytes, so non-utf-8 file names are preserved.
+        paths: Vec<PathBuf>,
     },
     /// The joystick functions expose connected joysticks and controllers,
     /// with both referred to as joysticks. It supports up to sixteen joysticks ([Joystick])
@@ -261,3 +282,48 @@ pub enum Event {
         connected: bool,
     },
 }
+
+/// The paths of an [Event::Drop], split by what they point to.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DroppedPaths {
+    pub files: Vec<PathBuf>,
+    pub directories: Vec<PathBuf>,
+    /// Paths that don't exist anymore or can't be accessed.
+    pub others: Vec<PathBuf>,
+}
+impl DroppedPaths {
+    /// Splits `paths` into files and directories (following symlinks) using [std::fs::metadata].
+    pub fn classify(paths: &[PathBuf]) -> Self {
+        let mut result = Self::default();
+        for path in paths {
+            match std::fs::metadata(path) {
+                Ok(meta) if meta.is_dir() => result.directories.push(path.clone()),
+                Ok(meta) if meta.is_file() => result.files.push(path.clone()),
+                _ => result.others.push(path.clone()),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn classify_dropped_paths() {
+        let dir = std::env::temp_dir();
+        let file = dir.join(format!("glfw_rust_drop_{}", std::process::id()));
+        std::fs::write(&file, b"dropped").unwrap();
+        let missing = dir.join("glfw_rust_drop_missing/not_here");
+        let classified = DroppedPaths::classify(&[file.clone(), dir.clone(), missing.clone()]);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            classified,
+            DroppedPaths {
+                files: vec![file],
+                directories: vec![dir],
+                others: vec![missing],
+            }
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::PoisonError;
 
 use tracing::error;
 
@@ -17,7 +18,7 @@ use tinyvec::TinyVec;
 /// Read more about initialization hints at <https://www.glfw.org/docs/latest/intro_guide.html#init_hints>
 ///
 /// For default values, look at the table at <https://www.glfw.org/docs/latest/intro_guide.html#init_hints_values>
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct EventLoopConfig {
     /// The platform that glfw will use.
     ///
@@ -36,8 +37,33 @@ pub struct EventLoopConfig {
     /// Otherwise, the [default_error_callback] will be used. [default_error_callback]
     /// will just log the errors using [tracing::error] macro.
     ///
+    /// Setting this disables [Self::on_error] and [Self::error_events], as they are implemented
+    /// by our own error callback.
+    ///
     /// <https://www.glfw.org/docs/latest/intro_guide.html#error_handling>
     pub error_callback: GLFWerrorfun,
+    /// If set, this closure is called with the errors that occur on the main thread.
+    ///
+    /// Errors from other threads (eg: with a context current on a render thread) are
+    /// logged with [default_error_callback] instead, as the closure is not [Send].
+    /// Errors that occur while the closure itself is running are logged too.
+    ///
+    /// The closure is called from an `extern "C"` function, so its panics are caught and
+    /// logged instead of unwinding into glfw.
+    ///
+    /// ```rust
+    /// # use glfw_rust::*;
+    /// let mut config = EventLoopConfig::default();
+    /// config.on_error = Some(Box::new(|error| eprintln!("glfw error: {error:?}")));
+    /// ```
+    pub on_error: Option<ErrorHandler>,
+    /// If true, errors on the main thread are also queued as [Event::Error], and returned
+    /// by [EventLoop::poll_events] related methods.
+    pub error_events: bool,
+    /// The number of recent errors to keep for [EventLoop::recent_errors]. 0 (default) disables it.
+    ///
+    /// Like [Self::on_error], this is ignored if [Self::error_callback] is set.
+    pub error_history: usize,
 
     /// specifies whether to also expose joystick hats as buttons, for compatibility
     /// with earlier versions of GLFW that did not have [EventLoop::get_joystick_hats].
@@ -58,6 +84,28 @@ pub struct EventLoopConfig {
     /// <https://www.glfw.org/docs/latest/intro_guide.html#init_hints_x11>
     pub x11_xcb_vk_surface: Option<bool>,
 }
+/// The closure type of [EventLoopConfig::on_error].
+pub type ErrorHandler = Box<dyn FnMut(GlfwError)>;
+impl std::fmt::Debug for EventLoopConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventLoopConfig")
+            .field("platform", &self.platform)
+            .field("error_callback", &self.error_callback)
+            .field(
+                "on_error",
+                &self.on_error.as_ref().map(|_| "FnMut(GlfwError)"),
+            )
+            .field("error_events", &self.error_events)
+            .field("error_history", &self.error_history)
+            .field("joystick_hat_buttons", &self.joystick_hat_buttons)
+            .field("angle_platform", &self.angle_platform)
+            .field("cocoa_chdir_resources", &self.cocoa_chdir_resources)
+            .field("cocoa_menubar", &self.cocoa_menubar)
+            .field("wayland_libdecor", &self.wayland_libdecor)
+            .field("x11_xcb_vk_surface", &self.x11_xcb_vk_surface)
+            .finish()
+    }
+}
 impl EventLoopConfig {
     /// This sets the window hints and logs any errors before returning the error.
     #[doc(alias = "glfwInitHint")]
@@ -65,6 +113,9 @@ impl EventLoopConfig {
         let EventLoopConfig {
             platform,
             error_callback,
+            on_error,
+            error_events,
+            error_history,
             joystick_hat_buttons,
             angle_platform,
             cocoa_chdir_resources,
@@ -73,7 +124,15 @@ impl EventLoopConfig {
             x11_xcb_vk_surface,
         } = self;
         clear_error();
-        glfwSetErrorCallback(Some(error_callback.unwrap_or(default_error_callback)));
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            data.error_handler.replace(on_error);
+            data.error_events.set(error_events);
+        });
+        // a custom error callback bypasses error_trampoline, so nothing would be recorded
+        *ERROR_HISTORY.lock().unwrap_or_else(PoisonError::into_inner) = (error_callback.is_none()
+            && error_history > 0)
+            .then(|| ErrorHistory::new(error_history));
+        glfwSetErrorCallback(Some(error_callback.unwrap_or(error_trampoline)));
         get_error().inspect_err(|e| error!("setting error callback failed: {e:?}"))?;
         if let Some(platform) = platform {
             glfwInitHint(GLFW_PLATFORM, platform as _);
@@ -136,6 +195,68 @@ pub unsafe extern "C" fn default_error_callback(code: i32, description: *const s
     };
     error!("code = {}; desc = {}", code, description);
 }
+thread_local! {
+    /// The error code that the running glfw call is expected to report on this thread.
+    ///
+    /// Set by [EventLoop::checked_expecting], and [error_trampoline] ignores errors with this code.
+    static EXPECTED_ERROR: Cell<Option<ErrorCode>> = const { Cell::new(None) };
+}
+/// The error callback used unless [EventLoopConfig::error_callback] is set.
+///
+/// Records the error in [EventLoop::recent_errors], if [EventLoopConfig::error_history] is set.
+///
+/// On the main thread, this forwards the error to [EventLoopConfig::on_error] and
+/// queues it as [Event::Error] if [EventLoopConfig::error_events] is set.
+/// If the error was not handled by the closure, it is logged with [default_error_callback].
+///
+/// Errors expected by [EventLoop::checked_expecting] are ignored, as the caller handles them.
+unsafe extern "C" fn error_trampoline(code: i32, description: *const std::ffi::c_char) {
+    if EXPECTED_ERROR
+        .try_with(|expected| expected.get() == Some(code.into()))
+        .unwrap_or_default()
+    {
+        return;
+    }
+    let error = GlfwError {
+        code: code.into(),
+        description: if description.is_null() {
+            String::new()
+        } else {
+            std::ffi::CStr::from_ptr(description)
+                .to_string_lossy()
+                .to_string()
+        },
+        context: None,
+    };
+    record_error(&error);
+    let handled = MAIN_THREAD_LOCAL_DATA
+        .try_with(|data| {
+            if data.error_events.get() && data.is_alive.get() {
+                data.push_event(Event::Error(error.clone()));
+            }
+            // fails if the error occurred inside the closure.
+            let Ok(mut handler) = data.error_handler.try_borrow_mut() else {
+                return false;
+            };
+            match handler.as_mut() {
+                Some(handler) => {
+                    // unwinding out of an extern "C" fn aborts the process.
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        handler(error.clone())
+                    }));
+                    if result.is_err() {
+                        error!("EventLoopConfig::on_error panicked while handling {error:?}");
+                    }
+                    true
+                }
+                None => false,
+            }
+        })
+        .unwrap_or_default();
+    if !handled {
+        default_error_callback(code, description);
+    }
+}
 
 /// This represents the entry point of this crate. It must be created, used and destroyed on main-thread.
 ///
@@ -152,14 +273,14 @@ pub unsafe extern "C" fn default_error_callback(code: i32, description: *const s
 /// let el = EventLoop::init(EventLoopConfig::default()).unwrap();
 ///
 /// let window = Window::new(el.clone(),Default::default(),800,600,"Hello World",None,None).unwrap();
-/// window.make_current();
+/// let current = window.make_current_scoped();
 /// while window.should_close() {
 ///     for (event_timestmp, event) in el.wait_events() {
 ///         // handle events
 ///     }
 ///     
 ///     // do some rendering
-///     window.swap_buffers();
+///     current.swap_buffers();
 /// # break;
 /// }
 /// ```
@@ -178,6 +299,11 @@ pub struct EventLoop {
     init_thread_id: std::thread::ThreadId,
     /// Proxy object that can be used for thread-safe glfw methods.
     proxy: EventLoopProxy,
+    /// Cursors created by [EventLoop::std_cursor].
+    ///
+    /// These are weak, as cursors keep the event loop alive. They live as long as
+    /// a window (or the user) uses them.
+    pub(crate) std_cursors: RefCell<HashMap<StdCursor, std::rc::Weak<Cursor>>>,
     /// top stop this from being moved to a different thread.
     _no_sync: std::marker::PhantomData<*const ()>,
 }
@@ -189,6 +315,18 @@ impl Drop for EventLoop {
             data.is_alive.set(false);
             data.events.take();
             data.monitors.take();
+            data.cascade_position.take();
+            data.cursor_positions.take();
+            data.animated_cursors.take();
+            data.pointer_locks.take();
+            // glfwTerminate destroys the deferred windows, so mark them as dead for any proxies.
+            for (window, _) in data.deferred_windows.take() {
+                let _guard = window.current_thread.lock();
+                if window.is_current.load(Ordering::Acquire) {
+                    error!("EventLoop is being dropped while a window is still current on a thread. This is UB.");
+                }
+                window.is_alive.store(false, Ordering::Release);
+            }
         });
         // if Arc::weak_count(&self.proxy.data) > 0 {
         //     error!("EventLoop is being dropped with more than one EventloopProxy still being alive. This is a bug.");
@@ -197,12 +335,20 @@ impl Drop for EventLoop {
         if std::thread::current().id() != self.init_thread_id {
             error!("EventLoop is being dropped from a different thread than it was initialized on. This is a bug.");
         }
-        
+
         unsafe {
             clear_error();
             glfwTerminate();
             log_error();
         };
+        MAIN_THREAD_LOCAL_DATA.with(|data| {
+            data.error_handler.take();
+            data.error_events.set(false);
+        });
+        ERROR_HISTORY
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
     }
 }
 impl Deref for EventLoop {
@@ -238,22 +384,38 @@ impl EventLoop {
             );
         });
 
+        // the handler and history would otherwise outlive the failed init, and receive
+        // errors until the next successful init.
+        let clear_error_state = || {
+            MAIN_THREAD_LOCAL_DATA.with(|main_glfw| {
+                main_glfw.error_handler.take();
+                main_glfw.error_events.set(false);
+            });
+            ERROR_HISTORY
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        };
         unsafe {
-            config
-                .set_hints()
-                .inspect_err(|e| error!("setting hints failed: {e:?}"))?;
+            config.set_hints().inspect_err(|e| {
+                error!("setting hints failed: {e:?}");
+                clear_error_state();
+            })?;
             // previous functions errors should have been cleared.
             assert_no_error();
             if glfwInit() != GLFW_TRUE {
-                return match get_error() {
-                    Ok(_) => Err(GlfwError::dead_context("glfw init failed with NO errors")),
-                    Err(error) => Err(error),
+                let error = match get_error() {
+                    Ok(_) => GlfwError::dead_context("glfw init failed with NO errors"),
+                    Err(error) => error,
                 };
+                clear_error_state();
+                return Err(error);
             }
             let data = Arc::new(AtomicBool::new(true));
             let el = Rc::new(Self {
                 init_thread_id: std::thread::current().id(),
                 proxy: EventLoopProxy { data },
+                std_cursors: Default::default(),
                 _no_sync: std::marker::PhantomData,
             });
             MAIN_THREAD_LOCAL_DATA.with(|main_glfw| {
@@ -264,6 +426,11 @@ impl EventLoop {
                 main_glfw.is_alive.set(true);
                 main_glfw.events.take();
                 main_glfw.monitors.take();
+                main_glfw.cascade_position.take();
+                main_glfw.cursor_positions.take();
+                main_glfw.animated_cursors.take();
+                main_glfw.pointer_locks.take();
+                main_glfw.deferred_windows.take();
                 // just to *really* make sure
                 let old_el = main_glfw.el.replace(Rc::downgrade(&el));
                 if old_el.upgrade().is_some() {
@@ -320,10 +487,38 @@ impl EventLoop {
     ///     el.checked(|| win.get_pos())
     /// }
     /// ```
+    ///
+    /// The returned error has its [GlfwError::context] set to the caller of this function.
+    #[track_caller]
     pub fn checked<T>(&self, f: impl FnOnce() -> T) -> GlfwResult<T> {
+        let caller = std::panic::Location::caller();
         clear_error();
         let result = f();
-        get_error().and(Ok(result))
+        get_error()
+            .inspect_err(|error| record_error_caller(error, caller))
+            .map_err(|error| error.with_context(caller))
+            .and(Ok(result))
+    }
+    /// Like [Self::checked], but for calls where an error with the `expected` code is a normal
+    /// outcome (eg: [ErrorCode::FormatUnavailable] for an empty clipboard).
+    ///
+    /// The expected error is returned as `Ok(None)`, and it doesn't reach
+    /// [EventLoopConfig::on_error], [Event::Error] or [Self::recent_errors] and isn't logged.
+    /// A custom [EventLoopConfig::error_callback] still receives it.
+    #[track_caller]
+    pub(crate) fn checked_expecting<T>(
+        &self,
+        expected: ErrorCode,
+        f: impl FnOnce() -> T,
+    ) -> GlfwResult<Option<T>> {
+        let previous = EXPECTED_ERROR.replace(Some(expected));
+        let result = self.checked(f);
+        EXPECTED_ERROR.set(previous);
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if error.code == expected => Ok(None),
+            Err(error) => Err(error),
+        }
     }
     /// 1. calls [clear_error].
     /// 2. calls the closure.
@@ -337,6 +532,7 @@ impl EventLoop {
         clear_error();
         let result = f();
         if let Err(error) = get_error() {
+            record_error_caller(&error, std::panic::Location::caller());
             tracing::error!(
                 "context = {} code = {}, description = {}",
                 std::panic::Location::caller(),
@@ -366,12 +562,14 @@ impl EventLoop {
     /// your app is unresponsive and may forcibly kill it.
     ///
     /// If you are a game-like app, you probably want to pump as many frames as you want, so, you will
-    /// use this function to get the events, react to them, draw to screen and present the surface (eg: [WindowProxy::swap_buffers]).
+    /// use this function to get the events, react to them, draw to screen and present the surface (eg: [CurrentGuard::swap_buffers]).
     ///
     /// But if you are a gui-app that only needs to draw in response to events, you are
     /// better off using [Self::wait_events] instead.
     pub fn poll_events(&self) -> Vec<(f64, Event)> {
         unsafe { glfwPollEvents() };
+        self.advance_animated_cursors();
+        destroy_released_windows();
         MAIN_THREAD_LOCAL_DATA.with(|main_glfw| main_glfw.events.take())
     }
     /// This function puts the calling thread to sleep until at least one event is available in the event queue.
@@ -386,8 +584,16 @@ impl EventLoop {
     /// even if there wasn't a gui event.
     ///
     /// If you would like to timeout the wait, use [Self::wait_events_timeout].
+    ///
+    /// If a window uses an [AnimatedCursor], this also returns (possibly without events)
+    /// when the next cursor frame is due.
     pub fn wait_events(&self) -> Vec<(f64, Event)> {
-        unsafe { glfwWaitEvents() };
+        match self.next_animated_cursor_timeout() {
+            Some(timeout) => unsafe { glfwWaitEventsTimeout(timeout) },
+            None => unsafe { glfwWaitEvents() },
+        }
+        self.advance_animated_cursors();
+        destroy_released_windows();
         MAIN_THREAD_LOCAL_DATA.with(|main_glfw| main_glfw.events.take())
     }
     /// This function puts the calling thread to sleep until at least one event is available in the event queue, or until the specified timeout is reached.
@@ -400,10 +606,15 @@ impl EventLoop {
     ///
     /// Just like [Self::wait_events], you can use [EventLoopProxy::post_empty_event] to
     /// make this function return earlier.
-    ///
-    ///
+    /// If a window uses an [AnimatedCursor], this returns earlier when the next cursor frame is due.
     pub fn wait_events_timeout(&self, timeout: f64) -> Vec<(f64, Event)> {
+        let timeout = match self.next_animated_cursor_timeout() {
+            Some(cursor_timeout) => timeout.min(cursor_timeout),
+            None => timeout,
+        };
         unsafe { glfwWaitEventsTimeout(timeout) };
+        self.advance_animated_cursors();
+        destroy_released_windows();
         MAIN_THREAD_LOCAL_DATA.with(|main_glfw| main_glfw.events.take())
     }
     /// This function returns whether raw mouse motion is supported on the current
@@ -522,7 +733,7 @@ impl EventLoop {
         }
         Some(TinyVec::from_iter(
             unsafe { std::slice::from_raw_parts(buttons, count as _) }
-                .into_iter()
+                .iter()
                 .map(|c| *c as i32 == GLFW_TRUE),
         ))
     }
@@ -563,7 +774,7 @@ impl EventLoop {
         }
         Some(TinyVec::from_iter(
             unsafe { std::slice::from_raw_parts(hats, count as _) }
-                .into_iter()
+                .iter()
                 .map(|h| JoystickHatState::from_bits(*h).unwrap()),
         ))
     }
@@ -863,20 +1074,43 @@ impl EventLoopProxy {
     ///
     /// We return a `Vec<CString>` because it is easier to just
     /// pass it directly to vulkan related APIs.
+    ///
+    /// # Panics
+    /// If vulkan is not supported. See [Self::try_get_required_instance_extensions].
     #[doc(alias = "glfwGetRequiredInstanceExtensions")]
     pub fn get_required_instance_extensions(&self) -> Vec<CString> {
         assert!(self.is_vulkan_supported());
+        self.try_get_required_instance_extensions()
+            .expect("failed to get required instance extensions")
+    }
+    /// Like [Self::get_required_instance_extensions], but returns [ErrorCode::ApiUnavailable]
+    /// instead of panicking if vulkan is not supported.
+    #[doc(alias = "glfwGetRequiredInstanceExtensions")]
+    pub fn try_get_required_instance_extensions(&self) -> GlfwResult<Vec<CString>> {
+        if !self.is_vulkan_supported() {
+            return Err(GlfwError {
+                code: ErrorCode::ApiUnavailable,
+                description: "glfw-rust: vulkan is not supported".to_string(),
+                context: None,
+            });
+        }
         let mut count = 0;
         let extensions =
             self.with_proxy_alive(|| unsafe { glfwGetRequiredInstanceExtensions(&mut count) });
         if count == 0 || extensions.is_null() {
-            return Vec::new();
+            return Ok(Vec::new());
         }
         unsafe { std::slice::from_raw_parts(extensions, count as _) }
-            .into_iter()
-            .map(|s| unsafe {
-                assert!(!s.is_null());
-                CStr::from_ptr(*s).to_owned()
+            .iter()
+            .map(|s| {
+                if s.is_null() {
+                    return Err(GlfwError {
+                        code: ErrorCode::PlatformError,
+                        description: "glfw-rust: null instance extension name".to_string(),
+                        context: None,
+                    });
+                }
+                Ok(unsafe { CStr::from_ptr(*s) }.to_owned())
             })
             .collect()
     }
@@ -896,6 +1130,9 @@ impl EventLoopProxy {
     ///
     /// This function is equivalent to calling vkGetInstanceProcAddr with
     /// a platform-specific query of the Vulkan loader as a fallback.
+    ///
+    /// # Safety
+    /// `instance` must be null or a valid vulkan instance.
     #[doc(alias = "glfwGetInstanceProcAddress")]
     pub unsafe fn get_instance_proc_addr_cstr(
         instance: VkInstance,
@@ -916,6 +1153,9 @@ impl EventLoopProxy {
     /// check whether Vulkan is at least minimally available and
     /// [Self::get_required_instance_extensions] to check what instance extensions
     /// are required.
+    ///
+    /// # Safety
+    /// `instance` and `device` must be valid vulkan handles.
     #[doc(alias = "glfwGetPhysicalDevicePresentationSupport")]
     pub unsafe fn get_physical_device_presentation_support(
         &self,
@@ -937,3 +1177,45 @@ impl EventLoopProxy {
         (code != -1).then_some(code)
     }
 }
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "testing")]
+    #[test]
+    fn expected_errors_are_not_reported() {
+        use super::*;
+        use std::cell::RefCell;
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let errors_in_callback = errors.clone();
+        let el = crate::testing::NullEventLoop::with_config(EventLoopConfig {
+            on_error: Some(Box::new(move |error| {
+                errors_in_callback.borrow_mut().push(error.code)
+            })),
+            error_events: true,
+            error_history: 4,
+            ..Default::default()
+        })
+        .unwrap();
+        let window = el.window(WindowConfig::default()).unwrap();
+        el.drain_events();
+        // 0 is not a valid window attribute
+        let invalid_attrib = || unsafe { glfwGetWindowAttrib(window.id().get_ptr(), 0) };
+
+        assert_eq!(
+            el.checked_expecting(ErrorCode::InvalidEnum, invalid_attrib),
+            Ok(None)
+        );
+        assert!(errors.borrow().is_empty());
+        assert!(el.drain_events().is_empty());
+        assert!(el.recent_errors().is_empty());
+
+        // other errors are still reported
+        let error = el
+            .checked_expecting(ErrorCode::FormatUnavailable, invalid_attrib)
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidEnum);
+        assert_eq!(*errors.borrow(), [ErrorCode::InvalidEnum]);
+        assert_eq!(el.recent_errors().len(), 1);
+        drop(window);
+    }
+}
@@ -0,0 +1,296 @@
+//! A decoder for Windows `.ico` (icon) and `.cur` (cursor) files.
+//!
+//! Both are containers of multiple images (usually of different sizes). Each image is
+//! either a BMP (without the file header) or a PNG. `.cur` files also store the hotspot
+//! of each image.
+//!
+//! PNG payloads need the `png` feature (enabled by default).
+use crate::*;
+
+/// An image of an `.ico`/`.cur` file. see [decode_ico]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IcoImage {
+    pub image: Image,
+    /// The hotspot of cursor images. None for `.ico` files.
+    pub hotspot: Option<(u32, u32)>,
+}
+fn invalid_ico(description: &str) -> GlfwError {
+    GlfwError {
+        code: ErrorCode::InvalidValue,
+        description: format!("glfw-rust: invalid ico/cur: {description}"),
+        context: None,
+    }
+}
+/// A bounds-checked little-endian reader, shared by the `.ico`/`.cur` and Xcursor parsers.
+///
+/// Offsets and lengths come from untrusted files, so every read checks for overflow and
+/// fails with the error of `invalid` (eg: [invalid_ico]) instead of panicking.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    invalid: fn(&str) -> GlfwError,
+}
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], invalid: fn(&str) -> GlfwError) -> Self {
+        Self { bytes, invalid }
+    }
+    /// The reader of the bytes starting at `offset`, to read a structure with its field offsets.
+    pub(crate) fn at(&self, offset: usize) -> GlfwResult<Reader<'a>> {
+        let bytes = self
+            .bytes
+            .get(offset..)
+            .ok_or_else(|| (self.invalid)("unexpected end of file"))?;
+        Ok(Self::new(bytes, self.invalid))
+    }
+    pub(crate) fn bytes(&self, offset: usize, len: usize) -> GlfwResult<&'a [u8]> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or_else(|| (self.invalid)("unexpected end of file"))
+    }
+    pub(crate) fn u16(&self, offset: usize) -> GlfwResult<u16> {
+        Ok(u16::from_le_bytes(
+            self.bytes(offset, 2)?.try_into().unwrap(),
+        ))
+    }
+    pub(crate) fn u32(&self, offset: usize) -> GlfwResult<u32> {
+        Ok(u32::from_le_bytes(
+            self.bytes(offset, 4)?.try_into().unwrap(),
+        ))
+    }
+}
+/// Decodes all the images of an `.ico` or `.cur` file, in the order they are stored.
+///
+/// Use the images with [Window::set_icon], which picks the best size for the platform.
+/// For cursors, see [Cursor::from_cur_bytes].
+///
+/// Returns [ErrorCode::InvalidValue] if the file is malformed and [ErrorCode::FormatUnavailable]
+/// for unsupported payloads (eg: PNG without the `png` feature or compressed BMPs).
+pub fn decode_ico(bytes: &[u8]) -> GlfwResult<Vec<IcoImage>> {
+    let reader = Reader::new(bytes, invalid_ico);
+    let kind = reader.u16(2)?;
+    if reader.u16(0)? != 0 || !(kind == 1 || kind == 2) {
+        return Err(invalid_ico("bad header"));
+    }
+    let is_cursor = kind == 2;
+    let count = reader.u16(4)? as usize;
+    let mut images = Vec::with_capacity(count);
+    for index in 0..count {
+        let entry = 6 + index * 16;
+        // in cur files, planes and bit count are replaced by the hotspot
+        let hotspot =
+            is_cursor.then_some((reader.u16(entry + 4)? as u32, reader.u16(entry + 6)? as u32));
+        let size = reader.u32(entry + 8)? as usize;
+        let offset = reader.u32(entry + 12)? as usize;
+        let payload = reader.bytes(offset, size)?;
+        let image = if payload.starts_with(b"\x89PNG\r\n\x1a\n") {
+            decode_png(payload)?
+        } else {
+            decode_bmp(payload)?
+        };
+        images.push(IcoImage { image, hotspot });
+    }
+    Ok(images)
+}
+#[cfg(feature = "png")]
+fn decode_png(payload: &[u8]) -> GlfwResult<Image> {
+    let png_error = |e: png::DecodingError| invalid_ico(&format!("bad png: {e}"));
+    let mut decoder = png::Decoder::new(payload);
+    // expand palettes/low bit depths/trns and strip 16 bit channels, so we get 8-bit
+    // gray, gray-alpha, rgb or rgba.
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+    let pixels: Vec<u8> = match info.color_type {
+        png::ColorType::Rgba => buffer[..info.buffer_size()].to_vec(),
+        png::ColorType::Rgb => buffer[..info.buffer_size()]
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer[..info.buffer_size()]
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer[..info.buffer_size()]
+            .iter()
+            .flat_map(|&p| [p, p, p, u8::MAX])
+            .collect(),
+        png::ColorType::Indexed => return Err(invalid_ico("png palette was not expanded")),
+    };
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+#[cfg(not(feature = "png"))]
+fn decode_png(_payload: &[u8]) -> GlfwResult<Image> {
+    Err(GlfwError {
+        code: ErrorCode::FormatUnavailable,
+        description: "glfw-rust: png payloads in ico/cur files need the png feature".to_string(),
+        context: None,
+    })
+}
+/// Decodes a BMP payload (a `BITMAPINFOHEADER` without the file header).
+///
+/// The height in the header is doubled, as the color (XOR) bitmap is followed by a 1-bit
+/// transparency (AND) mask. Both are stored bottom-up with rows padded to 4 bytes.
+fn decode_bmp(payload: &[u8]) -> GlfwResult<Image> {
+    /// `BI_RGB`, the only compression used by icons in practice.
+    const BI_RGB: u32 = 0;
+    let reader = Reader::new(payload, invalid_ico);
+    let header_size = reader.u32(0)? as usize;
+    let width = reader.u32(4)? as i32;
+    let double_height = reader.u32(8)? as i32;
+    let bit_count = reader.u16(14)?;
+    let compression = reader.u32(16)?;
+    let colors_used = reader.u32(32)? as usize;
+    if header_size < 40 || width <= 0 || double_height <= 0 || width > 256 || double_height > 512 {
+        return Err(invalid_ico("bad bmp header"));
+    }
+    if compression != BI_RGB {
+        return Err(GlfwError {
+            code: ErrorCode::FormatUnavailable,
+            description: format!("glfw-rust: unsupported bmp compression {compression} in ico/cur"),
+            context: None,
+        });
+    }
+    let (width, height) = (width as usize, double_height as usize / 2);
+    let palette_len = match bit_count {
+        1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+        1 | 4 | 8 => colors_used,
+        24 | 32 => 0,
+        _ => return Err(invalid_ico("unsupported bmp bit count")),
+    };
+    let palette = reader.bytes(header_size, palette_len * 4)?;
+    let row_len = |bits: usize| (width * bits).div_ceil(32) * 4;
+    let xor_offset = header_size + palette_len * 4;
+    let xor_row_len = row_len(bit_count as usize);
+    let and_offset = xor_offset + xor_row_len * height;
+    let and_row_len = row_len(1);
+    let xor = reader.bytes(xor_offset, xor_row_len * height)?;
+    // some encoders omit the AND mask of 32-bit images, as they have alpha
+    let and = reader.bytes(and_offset, and_row_len * height).ok();
+    if and.is_none() && bit_count != 32 {
+        return Err(invalid_ico("missing bmp mask"));
+    }
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    // rows are bottom-up
+    for y in (0..height).rev() {
+        let row = &xor[y * xor_row_len..][..xor_row_len];
+        for x in 0..width {
+            let (b, g, r, a) = match bit_count {
+                32 => (row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]),
+                24 => (row[x * 3], row[x * 3 + 1], row[x * 3 + 2], u8::MAX),
+                bits => {
+                    let bits = bits as usize;
+                    let bit = x * bits;
+                    let index = (row[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1) as u8;
+                    let color = palette
+                        .get(index as usize * 4..index as usize * 4 + 4)
+                        .ok_or_else(|| invalid_ico("bmp palette index out of range"))?;
+                    (color[0], color[1], color[2], u8::MAX)
+                }
+            };
+            pixels.extend_from_slice(&[r, g, b, a]);
+        }
+    }
+    // images without alpha (or 32-bit images with all alpha zero, from old encoders)
+    // use the AND mask for transparency.
+    let use_mask = bit_count != 32 || pixels.chunks_exact(4).all(|p| p[3] == 0);
+    if let (true, Some(and)) = (use_mask, and) {
+        for (y, row) in pixels.chunks_exact_mut(width * 4).enumerate() {
+            let mask = &and[(height - 1 - y) * and_row_len..][..and_row_len];
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let transparent = mask[x / 8] & (0x80 >> (x % 8)) != 0;
+                pixel[3] = if transparent { 0 } else { u8::MAX };
+            }
+        }
+    }
+    Ok(Image {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    })
+}
+impl Cursor {
+    /// Loads a cursor from the contents of a `.cur` file.
+    ///
+    /// The file may contain images of multiple sizes. We use the image closest
+    /// to `size` (in pixels, eg: 32), with the hotspot stored in the file.
+    /// `.ico` files are also accepted, with the hotspot at the top-left corner.
+    ///
+    /// see [decode_ico] for the errors.
+    pub fn from_cur_bytes(el: Rc<EventLoop>, bytes: &[u8], size: u32) -> GlfwResult<Self> {
+        let images = decode_ico(bytes)?;
+        let best = images
+            .iter()
+            .min_by_key(|image| image.image.width.abs_diff(size))
+            .ok_or_else(|| invalid_ico("no images"))?;
+        let (x_hot, y_hot) = best.hotspot.unwrap_or_default();
+        Cursor::try_new_from_pixels(
+            el,
+            best.image.width,
+            best.image.height,
+            &best.image.pixels,
+            x_hot as i32,
+            y_hot as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+    /// pixel at (x, y) of an image
+    fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+        let index = (y * image.width + x) as usize * 4;
+        image.pixels[index..index + 4].try_into().unwrap()
+    }
+    /// all the fixtures are 4x4 images, with a red top row, a transparent bottom-right
+    /// pixel and blue everywhere else. They are generated by `tests/fixtures/generate.py`.
+    fn check_fixture_image(image: &Image) {
+        assert_eq!((image.width, image.height), (4, 4));
+        assert_eq!(image.pixels.len(), 4 * 4 * 4);
+        for x in 0..4 {
+            assert_eq!(pixel(image, x, 0), RED);
+        }
+        assert_eq!(pixel(image, 0, 3), BLUE);
+        assert_eq!(pixel(image, 2, 2), BLUE);
+        assert_eq!(pixel(image, 3, 3)[3], CLEAR[3]);
+    }
+    #[test]
+    fn decode_bmp_icons() {
+        let images = decode_ico(include_bytes!("../tests/fixtures/bmp.ico")).unwrap();
+        // 32-bit with alpha, 24-bit, 8-bit, 4-bit and 1-bit bitmaps
+        assert_eq!(images.len(), 5);
+        for image in &images {
+            assert_eq!(image.hotspot, None);
+            check_fixture_image(&image.image);
+        }
+    }
+    #[cfg(feature = "png")]
+    #[test]
+    fn decode_png_icon() {
+        let images = decode_ico(include_bytes!("../tests/fixtures/png.ico")).unwrap();
+        assert_eq!(images.len(), 1);
+        check_fixture_image(&images[0].image);
+    }
+    #[test]
+    fn decode_cursor_hotspot() {
+        let images = decode_ico(include_bytes!("../tests/fixtures/cursor.cur")).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].hotspot, Some((1, 2)));
+        check_fixture_image(&images[0].image);
+    }
+    #[test]
+    fn reject_malformed_files() {
+        assert!(decode_ico(b"").is_err());
+        assert!(decode_ico(&[0, 0, 3, 0, 0, 0]).is_err());
+        let bytes = include_bytes!("../tests/fixtures/bmp.ico");
+        assert!(decode_ico(&bytes[..bytes.len() - 8]).is_err());
+    }
+}
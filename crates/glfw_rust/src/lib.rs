@@ -1,31 +1,47 @@
+mod attributes;
+mod clipboard;
+mod context;
 #[forbid(missing_docs)]
 mod cursor;
+mod dpi;
+mod error_history;
 mod event;
 mod event_loop;
+mod ico;
 mod monitor;
 mod native;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod types;
 mod version;
 mod window;
+mod xcursor;
 
 use std::{
     cell::{Cell, RefCell},
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ffi::{CStr, CString},
     rc::Rc,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, OnceLock,
     },
 };
 
+pub use attributes::*;
+pub use clipboard::*;
+pub use context::*;
 pub use cursor::*;
+pub use dpi::*;
+pub use error_history::*;
 pub use event::*;
 pub use event_loop::*;
+pub use ico::*;
 pub use monitor::*;
 pub use types::*;
 pub use version::*;
 pub use window::*;
+pub use xcursor::*;
 pub(crate) mod ffi {
     pub use glfw_rust_sys::*;
 }
@@ -50,6 +66,13 @@ thread_local! {
             is_alive: Cell::new(false),
             events: RefCell::new(Vec::new()),
             monitors: RefCell::new(HashSet::new()),
+            cascade_position: Cell::new(None),
+            cursor_positions: RefCell::new(HashMap::new()),
+            animated_cursors: RefCell::new(Vec::new()),
+            pointer_locks: RefCell::new(HashMap::new()),
+            error_handler: RefCell::new(None),
+            deferred_windows: RefCell::new(Vec::new()),
+            error_events: Cell::new(false),
             el: std::rc::Weak::new().into(),
         }
     };
@@ -82,6 +105,31 @@ pub(crate) struct ThreadLocalEventLoopData {
     /// Any of the monitor related functions will check (for correctness) the liveness
     /// of a monitor using [EventLoop::is_monitor_alive] (which internally checks this set).
     pub monitors: RefCell<HashSet<*mut ffi::GLFWmonitor>>,
+    /// The position of the last window placed with [WindowPlacement::Cascade].
+    ///
+    /// The next cascaded window (without a parent) will be placed relative to this.
+    pub cascade_position: Cell<Option<(i32, i32)>>,
+    /// The position of the last [Event::CursorPos] of each window.
+    ///
+    /// Used to add the cursor position to [Event::Drop]. Entries are removed when the window is dropped.
+    pub cursor_positions: RefCell<HashMap<WindowId, (f64, f64)>>,
+    /// Windows using an [AnimatedCursor]. Advanced by [EventLoop::poll_events] related methods.
+    pub animated_cursors: RefCell<Vec<AnimatedCursorState>>,
+    /// Windows with an active [PointerLock]. Used by the cursor position and focus callbacks.
+    pub pointer_locks: RefCell<HashMap<WindowId, PointerLockState>>,
+    /// The closure from [EventLoopConfig::on_error], called by the error callback.
+    ///
+    /// Unlike other fields, this is set before `glfwInit`, so that it sees initialization errors.
+    pub error_handler: RefCell<Option<ErrorHandler>>,
+    /// [EventLoopConfig::error_events]
+    pub error_events: Cell<bool>,
+    /// Windows that must only be destroyed later: the hidden windows of [SharedContext]s,
+    /// and dropped [Window]s that were still current on another thread.
+    ///
+    /// The [std::sync::Weak] is dead once the [SharedContext] is dropped (and always dead for
+    /// dropped windows), and then the window is destroyed by [destroy_released_windows]
+    /// as soon as it is not current anywhere.
+    pub deferred_windows: RefCell<Vec<(Arc<WindowData>, std::sync::Weak<()>)>>,
     /// This is a weak reference to event loop. We don't really use this for anything.
     /// But on [EventLoop::init], we check if there's still a strong reference to this
     /// data, just to *really* ensure that there's no bugs.
@@ -121,6 +169,7 @@ pub(crate) fn push_event_to_thread_local(event: event::Event) {
 #[track_caller]
 pub(crate) fn log_error() {
     if let Err(error) = get_error() {
+        record_error_caller(&error, std::panic::Location::caller());
         tracing::error!(
             "context = {} code = {}, description = {}",
             std::panic::Location::caller(),
@@ -171,14 +220,14 @@ pub fn get_error() -> GlfwResult<()> {
     }
     Err(GlfwError {
         code: code.into(),
-        description: description
-            .is_null()
-            .then_some(String::new())
-            .unwrap_or_else(|| {
-                unsafe { std::ffi::CStr::from_ptr(description) }
-                    .to_string_lossy()
-                    .to_string()
-            }),
+        description: if description.is_null() {
+            String::new()
+        } else {
+            unsafe { std::ffi::CStr::from_ptr(description) }
+                .to_string_lossy()
+                .to_string()
+        },
+        context: None,
     })
 }
 thread_local! {
@@ -224,6 +273,12 @@ impl ThreadLocalContext {
             None
         }
     }
+    /// Returns the data of the current context on this thread (none if there is no current context)
+    ///
+    /// Used by [CurrentGuard] to restore the previous context.
+    pub fn get_current_data(&self) -> Option<Arc<WindowData>> {
+        self.is_any_current().then(|| self.data.borrow().clone())
+    }
     /// returns a new uncurrent default object.
     /// only useful for initializing [`LOCAL_GL_CONTEXT`]
     pub fn new_uncurrent() -> Self {
@@ -235,6 +290,10 @@ impl ThreadLocalContext {
                 is_alive: AtomicBool::new(false),
                 client_api: ClientApi::NoAPI,
                 context_creation_api: None,
+                context_robustness: Robustness::No,
+                context_info: OnceLock::new(),
+                gl_debug_filters: Mutex::new(vec![]),
+                swap_interval: Mutex::new(None),
             })
             .into(),
             is_any_current: Cell::new(false),
@@ -249,17 +308,37 @@ impl ThreadLocalContext {
     /// * if the window is not alive
     /// * if the window is current on a different thread
     pub fn make_current(&self, new_data: Arc<WindowData>) {
+        self.try_make_current(new_data)
+            .expect("failed to make window current")
+    }
+    /// Like [Self::make_current], but returns an error instead of panicking.
+    pub fn try_make_current(&self, new_data: Arc<WindowData>) -> GlfwResult<()> {
         let is_current = self.is_any_current.get();
         // if the context is already current, early return.
         if is_current && Arc::ptr_eq(&new_data, &self.data.borrow()) {
-            return;
+            return Ok(());
         }
         // now, we know that data is not current or there's a different current context.
         let mut guard = new_data.current_thread.lock().unwrap();
         // check if the window is still alive
-        assert!(new_data.is_alive.load(Ordering::Acquire));
+        if !new_data.is_alive.load(Ordering::Acquire) {
+            return Err(GlfwError {
+                code: ErrorCode::Crate(CrateError::WindowDead),
+                description: "glfw-rust: cannot make a destroyed window current".to_string(),
+                context: None,
+            });
+        }
         // if data is already current on a different thread, then this is UB
-        assert!(!new_data.is_current.load(Ordering::Acquire));
+        if new_data.is_current.load(Ordering::Acquire) {
+            return Err(GlfwError {
+                code: ErrorCode::Crate(CrateError::ContextCurrentElsewhere),
+                description: format!(
+                    "glfw-rust: window is already current on thread {:?}",
+                    *guard
+                ),
+                context: None,
+            });
+        }
         unsafe {
             ffi::glfwMakeContextCurrent(new_data.window);
         }
@@ -280,6 +359,7 @@ impl ThreadLocalContext {
         // don't forget to set the thread local's data, so it knows who is current for future calls
         self.data.replace(new_data);
         self.is_any_current.set(true);
+        Ok(())
     }
     /// Make the provided window non-current.
     /// If no window is provided, then any current context on this thread is made non-current.
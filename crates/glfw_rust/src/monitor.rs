@@ -157,6 +157,32 @@ impl EventLoop {
         })?;
         Ok([width, height])
     }
+    /// The dots per inch of the monitor, calculated from [Self::get_monitor_physical_size]
+    /// and the current video mode ([Self::get_video_mode]).
+    ///
+    /// The video mode is in screen coordinates, so on platforms where screen coordinates
+    /// are scaled (macOS/Wayland), this is the dpi of screen coordinates and not pixels.
+    ///
+    /// As the physical size may be inaccurate (see [Self::get_monitor_physical_size]),
+    /// prefer [Self::get_monitor_content_scale] for scaling UI.
+    ///
+    /// Returns [ErrorCode::FeatureUnavailable] if the monitor doesn't report its physical size.
+    pub fn get_monitor_dpi(&self, monitor: MonitorId) -> GlfwResult<[f32; 2]> {
+        let [width_mm, height_mm] = self.get_monitor_physical_size(monitor)?;
+        if width_mm <= 0 || height_mm <= 0 {
+            return Err(GlfwError {
+                code: ErrorCode::FeatureUnavailable,
+                description: format!("{monitor:?} monitor did not report its physical size"),
+                context: None,
+            });
+        }
+        let mode = self.get_video_mode(monitor)?;
+        const MM_PER_INCH: f32 = 25.4;
+        Ok([
+            mode.width as f32 * MM_PER_INCH / width_mm as f32,
+            mode.height as f32 * MM_PER_INCH / height_mm as f32,
+        ])
+    }
     /// This function retrieves the content scale for the specified monitor. The content scale is the ratio between the current DPI and the platform's default DPI. This is especially important for text and any UI elements. If the pixel dimensions of your UI scaled by this look appropriate on your machine then it should appear at a reasonable size on other machines regardless of their DPI and scaling settings. This relies on the system DPI and scaling settings being somewhat correct.
     /// The content scale may depend on both the monitor resolution and pixel density and on user settings. It may be very different from the raw DPI calculated from the physical size and current resolution.
     ///
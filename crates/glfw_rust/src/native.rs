@@ -1,5 +1,24 @@
 use crate::*;
 
+/// The error returned by the primary selection functions on platforms other than x11.
+fn primary_selection_unavailable() -> GlfwError {
+    GlfwError {
+        code: ErrorCode::FeatureUnavailable,
+        description: "glfw-rust: primary selection is only available on x11".to_string(),
+        context: None,
+    }
+}
+#[cfg(not(all(not(target_os = "macos"), unix)))]
+impl EventLoop {
+    /// Only available on x11. Returns [ErrorCode::FeatureUnavailable] on this platform.
+    pub fn set_primary_selection(&self, _text: &str) -> GlfwResult<()> {
+        Err(primary_selection_unavailable())
+    }
+    /// Only available on x11. Returns [ErrorCode::FeatureUnavailable] on this platform.
+    pub fn get_primary_selection(&self) -> GlfwResult<String> {
+        Err(primary_selection_unavailable())
+    }
+}
 impl EventLoopProxy {
     fn with_platform<T>(&self, p: Platform, f: impl FnOnce() -> T) -> GlfwResult<T> {
         self.with_proxy_alive(|| {
@@ -9,9 +28,123 @@ impl EventLoopProxy {
             Err(GlfwError {
                 code: ErrorCode::PlatformError,
                 description: format!("glfw-rust: This is not {p:?} platform"),
+                context: None,
             })
         })?;
-        self.with_alive_checked(|| f())
+        self.with_alive_checked(f)
+    }
+}
+#[cfg(all(not(target_os = "macos"), unix))]
+mod linux_common {
+    use crate::ffi::*;
+    use crate::*;
+    impl EventLoopProxy {
+        pub(crate) fn with_x11<T>(&self, f: impl FnOnce() -> T) -> GlfwResult<T> {
+            self.with_platform(Platform::X11, f)
+        }
+        pub(crate) fn with_wayland<T>(&self, f: impl FnOnce() -> T) -> GlfwResult<T> {
+            self.with_platform(Platform::Wayland, f)
+        }
+    }
+    impl EventLoop {
+        /// Runs `f` if this is x11, otherwise returns [ErrorCode::FeatureUnavailable].
+        fn with_x11_selection<T>(&self, f: impl FnOnce() -> GlfwResult<T>) -> GlfwResult<T> {
+            self.with_x11(f)
+                .map_err(|_| super::primary_selection_unavailable())?
+        }
+        /// Sets the x11 primary selection (pasted with middle-click) to `text`.
+        ///
+        /// Returns [ErrorCode::FeatureUnavailable] on other platforms and
+        /// [CrateError::InteriorNul] if the text contains a null byte.
+        #[doc(alias = "glfwSetX11SelectionString")]
+        pub fn set_primary_selection(&self, text: &str) -> GlfwResult<()> {
+            let text = CString::new(text).map_err(|_| GlfwError {
+                code: ErrorCode::Crate(CrateError::InteriorNul),
+                description: "glfw-rust: selection text contains a null byte".to_string(),
+                context: None,
+            })?;
+            self.with_x11_selection(|| {
+                self.checked(|| unsafe { glfwSetX11SelectionString(text.as_ptr()) })
+            })
+        }
+        /// Returns the contents of the x11 primary selection (the text selected anywhere
+        /// on the desktop, which is pasted with middle-click).
+        ///
+        /// Like [Window::get_clipboard_string], if the selection is empty or can't be
+        /// converted to a string, Err is returned and a [ErrorCode::FormatUnavailable]
+        /// error is generated.
+        /// Returns [ErrorCode::FeatureUnavailable] on other platforms.
+        #[doc(alias = "glfwGetX11SelectionString")]
+        pub fn get_primary_selection(&self) -> GlfwResult<String> {
+            self.with_x11_selection(|| {
+                self.checked(|| unsafe { clipboard_text(glfwGetX11SelectionString()) })
+            })?
+            // glfw reports an error for an empty selection, but don't rely on that.
+            .ok_or_else(|| empty_clipboard("primary selection"))
+        }
+    }
+    impl Window {
+        /// The `GLXContext` of the window.
+        ///
+        /// Errors with [ErrorCode::NoWindowContext] if the window doesn't have a glx context
+        /// (eg: it uses [ContextCreationApi::Egl] or [ClientApi::NoAPI]).
+        #[doc(alias = "glfwGetGLXContext")]
+        pub fn get_glx_context(&self) -> GlfwResult<usize> {
+            self.with_x11(|| {
+                self.el
+                    .checked(|| unsafe { glfwGetGLXContext(self.id().get_ptr()) })
+            })?
+        }
+        /// The `GLXWindow` of the window.
+        ///
+        /// Errors with [ErrorCode::NoWindowContext] if the window doesn't have a glx context.
+        #[doc(alias = "glfwGetGLXWindow")]
+        pub fn get_glx_window(&self) -> GlfwResult<usize> {
+            self.with_x11(|| {
+                self.el
+                    .checked(|| unsafe { glfwGetGLXWindow(self.id().get_ptr()) })
+            })?
+        }
+    }
+    impl EventLoopProxy {
+        /// EGL is available on both x11 and wayland.
+        fn with_egl<T>(&self, f: impl FnOnce() -> T) -> GlfwResult<T> {
+            match self.get_platform() {
+                Platform::Wayland => self.with_wayland(f),
+                _ => self.with_x11(f),
+            }
+        }
+        /// The `EGLDisplay` used by glfw.
+        ///
+        /// This is `EGL_NO_DISPLAY` (null) if glfw didn't initialize EGL yet, which happens
+        /// when the first window with an EGL context is created.
+        #[doc(alias = "glfwGetEGLDisplay")]
+        pub fn get_egl_display(&self) -> GlfwResult<*mut std::ffi::c_void> {
+            self.with_egl(|| unsafe { glfwGetEGLDisplay() })
+        }
+    }
+    impl Window {
+        /// The `EGLContext` of the window.
+        ///
+        /// Errors with [ErrorCode::NoWindowContext] if the window doesn't have an EGL context.
+        /// see [ContextCreationApi::Egl].
+        #[doc(alias = "glfwGetEGLContext")]
+        pub fn get_egl_context(&self) -> GlfwResult<*mut std::ffi::c_void> {
+            self.with_egl(|| {
+                self.el
+                    .checked(|| unsafe { glfwGetEGLContext(self.id().get_ptr()) })
+            })?
+        }
+        /// The `EGLSurface` of the window.
+        ///
+        /// Errors with [ErrorCode::NoWindowContext] if the window doesn't have an EGL context.
+        #[doc(alias = "glfwGetEGLSurface")]
+        pub fn get_egl_surface(&self) -> GlfwResult<*mut std::ffi::c_void> {
+            self.with_egl(|| {
+                self.el
+                    .checked(|| unsafe { glfwGetEGLSurface(self.id().get_ptr()) })
+            })?
+        }
     }
 }
 #[cfg(all(not(target_os = "macos"), unix, feature = "rwh"))]
@@ -22,9 +155,6 @@ mod linux {
     use crate::*;
     use raw_window_handle::*;
     impl EventLoopProxy {
-        fn with_x11<T>(&self, f: impl FnOnce() -> T) -> GlfwResult<T> {
-            self.with_platform(Platform::X11, f)
-        }
         pub fn get_x11_display(&self) -> GlfwResult<*mut std::ffi::c_void> {
             self.with_x11(|| unsafe { glfwGetX11Display() })
         }
@@ -120,9 +250,6 @@ mod linux {
         }
     }
     impl EventLoopProxy {
-        fn with_wayland<T>(&self, f: impl FnOnce() -> T) -> GlfwResult<T> {
-            self.with_platform(Platform::Wayland, f)
-        }
         pub fn get_wayland_display(&self) -> GlfwResult<*mut std::ffi::c_void> {
             self.with_wayland(|| unsafe { glfwGetWaylandDisplay().cast_mut() })
         }
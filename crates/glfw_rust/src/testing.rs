@@ -0,0 +1,157 @@
+//! Helpers to test code that uses this crate, without a display server.
+//!
+//! [NullEventLoop] initializes glfw on the [Platform::Null] platform, which has a single
+//! fake monitor and windows that only exist in memory. Window operations like
+//! [Window::set_size] or [Window::focus] emit their events immediately, so the events
+//! are the same on every run.
+//!
+//! ```rust,no_run
+//! use glfw_rust::{testing::NullEventLoop, *};
+//! let el = NullEventLoop::new().unwrap();
+//! let window = el.window(WindowConfig::default()).unwrap();
+//! window.set_size(100, 100);
+//! assert!(el.drain_events().contains(&Event::Size {
+//!     window: window.id(),
+//!     width: 100,
+//!     height: 100
+//! }));
+//! ```
+//!
+//! The null platform creates OpenGL contexts with OSMesa, so windows with the default
+//! [ClientApi] fail with [ErrorCode::ApiUnavailable] if OSMesa is not installed.
+//! [NullEventLoop::window] creates windows without a context by default, and
+//! [NullEventLoop::osmesa_window] creates windows with a context, if OSMesa is available.
+use std::ops::Deref;
+use std::sync::MutexGuard;
+
+use crate::*;
+
+/// glfw is a process-wide library, but tests run in parallel on different threads.
+/// So, only one [NullEventLoop] can be alive at a time.
+static NULL_EVENT_LOOP_LOCK: Mutex<()> = Mutex::new(());
+
+/// The size of windows created by [NullEventLoop::window].
+pub const TEST_WINDOW_SIZE: (u32, u32) = (640, 480);
+/// The title of windows created by [NullEventLoop::window].
+pub const TEST_WINDOW_TITLE: &str = "glfw-rust test window";
+
+/// An [EventLoop] on the [Platform::Null] platform. Derefs to [EventLoop].
+///
+/// Creating one blocks until all other [NullEventLoop]s (eg: in other tests) are dropped.
+///
+/// # Panics
+/// On drop, if there are still other references to the event loop (eg: a [Window]
+/// or [Cursor] that outlives this). glfw is only terminated after the last reference
+/// is dropped, which would break the next test.
+pub struct NullEventLoop {
+    el: Rc<EventLoop>,
+    _lock: MutexGuard<'static, ()>,
+}
+impl std::fmt::Debug for NullEventLoop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NullEventLoop")
+            .field("el", &self.el)
+            .finish_non_exhaustive()
+    }
+}
+impl Deref for NullEventLoop {
+    type Target = EventLoop;
+    fn deref(&self) -> &Self::Target {
+        &self.el
+    }
+}
+impl Drop for NullEventLoop {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            assert_eq!(
+                Rc::strong_count(&self.el),
+                1,
+                "NullEventLoop was dropped while windows or cursors still use it"
+            );
+        }
+    }
+}
+impl NullEventLoop {
+    /// Initializes glfw on the null platform with the default config.
+    pub fn new() -> GlfwResult<Self> {
+        Self::with_config(EventLoopConfig::default())
+    }
+    /// Initializes glfw with the `config`, but with [EventLoopConfig::platform] set to [Platform::Null].
+    pub fn with_config(config: EventLoopConfig) -> GlfwResult<Self> {
+        // a failed test would poison the lock, but the event loop was still dropped.
+        let lock = NULL_EVENT_LOOP_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let el = EventLoop::init(EventLoopConfig {
+            platform: Some(Platform::Null),
+            ..config
+        })?;
+        el.set_time(0.0);
+        Ok(Self { el, _lock: lock })
+    }
+    /// The event loop, for functions that need an `Rc<EventLoop>`.
+    pub fn el(&self) -> &Rc<EventLoop> {
+        &self.el
+    }
+    /// Creates a window of [TEST_WINDOW_SIZE] with the `config`.
+    ///
+    /// Unless [WindowConfig::client_api] is set, the window has no context ([ClientApi::NoAPI]).
+    pub fn window(&self, mut config: WindowConfig<'_>) -> GlfwResult<Window> {
+        config.client_api.get_or_insert(ClientApi::NoAPI);
+        let (width, height) = TEST_WINDOW_SIZE;
+        Window::new(
+            self.el.clone(),
+            config,
+            width,
+            height,
+            TEST_WINDOW_TITLE,
+            None,
+            None,
+        )
+    }
+    /// Creates a window of [TEST_WINDOW_SIZE] with an OSMesa OpenGL context and the `config`.
+    ///
+    /// [WindowConfig::client_api] defaults to [ClientApi::OpenGL] and
+    /// [WindowConfig::context_creation_api] to [ContextCreationApi::Osmesa].
+    ///
+    /// Returns `None` if OSMesa is not installed (and prints that the test is skipped),
+    /// so that tests can skip themselves:
+    /// ```rust,no_run
+    /// # use glfw_rust::{testing::NullEventLoop, *};
+    /// let el = NullEventLoop::new().unwrap();
+    /// let Some(window) = el.osmesa_window(WindowConfig::default()) else {
+    ///     return;
+    /// };
+    /// ```
+    ///
+    /// # Panics
+    /// If the window creation fails for any other reason.
+    pub fn osmesa_window(&self, mut config: WindowConfig<'_>) -> Option<Window> {
+        config.client_api.get_or_insert(ClientApi::OpenGL);
+        config
+            .context_creation_api
+            .get_or_insert(ContextCreationApi::Osmesa);
+        match self.window(config) {
+            Ok(window) => Some(window),
+            Err(error) if error.code == ErrorCode::ApiUnavailable => {
+                eprintln!("skipping test, OSMesa is not available: {error}");
+                None
+            }
+            Err(error) => panic!("failed to create OSMesa window: {error}"),
+        }
+    }
+    /// Queues an event, as if it was emitted by glfw.
+    ///
+    /// Useful for events that the null platform can't emit, like key presses or file drops.
+    pub fn inject_event(&self, event: Event) {
+        push_event_to_thread_local(event);
+    }
+    /// Polls for events and returns them without timestamps.
+    pub fn drain_events(&self) -> Vec<Event> {
+        self.el
+            .poll_events()
+            .into_iter()
+            .map(|(_, event)| event)
+            .collect()
+    }
+}
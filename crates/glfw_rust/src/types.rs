@@ -132,6 +132,10 @@ pub enum ContextCreationApi {
     #[default]
     Native = GLFW_NATIVE_CONTEXT_API,
     Egl = GLFW_EGL_CONTEXT_API,
+    /// Mesa's offscreen software renderer. Works without any display server (eg: with [Platform::Null]).
+    ///
+    /// With the `osmesa` feature, read the rendered buffers with `WindowProxy::read_color_buffer`.
+    #[doc(alias = "OSMesa")]
     Osmesa = GLFW_OSMESA_CONTEXT_API,
 }
 impl TryFrom<i32> for ContextCreationApi {
@@ -210,6 +214,8 @@ impl TryFrom<i32> for OpenGLProfile {
 pub struct GlfwError {
     pub code: ErrorCode,
     pub description: String,
+    /// Where the error was caught, if known. eg: the caller of [EventLoop::checked].
+    pub context: Option<&'static std::panic::Location<'static>>,
 }
 /// For more verbose documentation for each error code,
 /// open <https://www.glfw.org/docs/latest/group__errors.html> and scroll down.
@@ -258,6 +264,24 @@ pub enum ErrorCode {
     /// Platform unavailable or no matching platform was found.
     PlatformUnavailable = GLFW_PLATFORM_UNAVAILABLE,
     Custom(i32),
+    /// An error detected by this crate, rather than by glfw.
+    Crate(CrateError),
+}
+/// Errors detected by this crate (as opposed to glfw), used with [ErrorCode::Crate].
+///
+/// These are returned by the `try_*` variants of functions that would otherwise panic.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum CrateError {
+    /// A string passed to glfw contains a null byte.
+    InteriorNul,
+    /// The length of a pixel buffer doesn't match its dimensions.
+    InvalidPixelBuffer,
+    /// The window is already current on a different thread.
+    ContextCurrentElsewhere,
+    /// The window was already destroyed.
+    WindowDead,
+    /// glfw returned an invalid value for a window attribute.
+    InvalidWindowAttribute,
 }
 impl From<i32> for ErrorCode {
     fn from(value: i32) -> Self {
@@ -290,15 +314,73 @@ impl GlfwError {
         Self {
             code: ErrorCode::PlatformError,
             description: format!("At {context}, {monitor:?} monitor is no longer alive"),
+            context: None,
         }
     }
     pub fn dead_context(context: &str) -> Self {
         Self {
             code: ErrorCode::NotInitialized,
             description: format!("At {context}, glfw is dead"),
+            context: None,
+        }
+    }
+    /// Sets the [Self::context], if it isn't set already.
+    pub fn with_context(mut self, context: &'static std::panic::Location<'static>) -> Self {
+        self.context.get_or_insert(context);
+        self
+    }
+    /// Whether the platform (or driver) doesn't support what was requested.
+    ///
+    /// Retrying won't help, but falling back to a different api, version, format or feature might.
+    pub fn is_platform_unsupported(&self) -> bool {
+        matches!(
+            self.code,
+            ErrorCode::ApiUnavailable
+                | ErrorCode::VersionUnavailable
+                | ErrorCode::FormatUnavailable
+                | ErrorCode::CursorUnavailable
+                | ErrorCode::FeatureUnavailable
+                | ErrorCode::FeatureUnimplemented
+                | ErrorCode::PlatformUnavailable
+        )
+    }
+    /// Whether the error might go away if the operation is retried later.
+    ///
+    /// eg: [ErrorCode::PlatformError] can be caused by the compositor or the X server being
+    /// busy, and [ErrorCode::OutOfMemory] by memory pressure.
+    pub fn is_transient(&self) -> bool {
+        matches!(self.code, ErrorCode::PlatformError | ErrorCode::OutOfMemory)
+    }
+    /// Whether the error is caused by incorrect usage of the api.
+    ///
+    /// eg: invalid arguments, or calling context functions without a current context.
+    pub fn is_usage_error(&self) -> bool {
+        matches!(
+            self.code,
+            ErrorCode::NotInitialized
+                | ErrorCode::NoCurrentContext
+                | ErrorCode::InvalidEnum
+                | ErrorCode::InvalidValue
+                | ErrorCode::NoWindowContext
+                | ErrorCode::Crate(
+                    CrateError::InteriorNul
+                        | CrateError::InvalidPixelBuffer
+                        | CrateError::ContextCurrentElsewhere
+                        | CrateError::WindowDead
+                )
+        )
+    }
+}
+impl std::fmt::Display for GlfwError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "glfw error {}: {}", self.code, self.description)?;
+        if let Some(context) = self.context {
+            write!(f, " (at {context})")?;
         }
+        Ok(())
     }
 }
+impl std::error::Error for GlfwError {}
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -722,6 +804,53 @@ impl TryFrom<i32> for Key {
     }
 }
 
+/// The swap interval of a gl context. see [CurrentGuard::set_swap_interval]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwapInterval {
+    /// Swap immediately, without waiting for vertical blank. May cause tearing.
+    Immediate,
+    /// Wait for `n` vertical blanks before swapping. `Vsync(1)` is the usual vsync.
+    Vsync(u32),
+    /// Wait for `n` vertical blanks, but swap immediately if the frame is late (tearing instead
+    /// of stuttering). `Adaptive(1)` is the usual adaptive vsync.
+    ///
+    /// Needs `WGL_EXT_swap_control_tear` or `GLX_EXT_swap_control_tear`,
+    /// otherwise [CurrentGuard::set_swap_interval] falls back to `Vsync(n)`.
+    Adaptive(u32),
+}
+impl SwapInterval {
+    pub(crate) fn to_glfw(self) -> i32 {
+        match self {
+            SwapInterval::Immediate => 0,
+            SwapInterval::Vsync(n) => n.try_into().unwrap_or(i32::MAX),
+            // glfw takes the negated interval. 0 would disable vsync, so use at least 1.
+            SwapInterval::Adaptive(n) => (-i64::from(n.max(1))).max(i32::MIN.into()) as i32,
+        }
+    }
+}
+/// The interval as passed to glfwSwapInterval: 0 is [SwapInterval::Immediate], positive values
+/// are [SwapInterval::Vsync] and negative values are [SwapInterval::Adaptive] (`-1` is `Adaptive(1)`).
+impl From<i32> for SwapInterval {
+    fn from(interval: i32) -> Self {
+        match interval {
+            0 => SwapInterval::Immediate,
+            n if n > 0 => SwapInterval::Vsync(n as u32),
+            n => SwapInterval::Adaptive(n.unsigned_abs()),
+        }
+    }
+}
+/// An image with 8-bit RGBA pixels, arranged in rows from top to bottom.
+///
+/// The pixels are non-premultiplied, with the red channel first. The same layout is used
+/// by [Cursor::new_from_pixels].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// `width * height * 4` bytes.
+    pub pixels: Vec<u8>,
+}
+
 mod test {
     #[test]
     fn test_last_enums() {
@@ -735,4 +864,19 @@ mod test {
             super::Joystick::Joystick16 as _
         );
     }
+    #[test]
+    fn swap_interval_round_trip() {
+        use super::SwapInterval;
+        for interval in [0, 1, 2, -1, -2, i32::MAX, i32::MIN] {
+            assert_eq!(SwapInterval::from(interval).to_glfw(), interval);
+        }
+        assert_eq!(SwapInterval::from(-1), SwapInterval::Adaptive(1));
+        assert_eq!(SwapInterval::from(-3), SwapInterval::Adaptive(3));
+        assert_eq!(SwapInterval::from(2), SwapInterval::Vsync(2));
+        assert_eq!(SwapInterval::from(0), SwapInterval::Immediate);
+        // out of range values are clamped
+        assert_eq!(SwapInterval::Vsync(u32::MAX).to_glfw(), i32::MAX);
+        assert_eq!(SwapInterval::Adaptive(u32::MAX).to_glfw(), i32::MIN);
+        assert_eq!(SwapInterval::Adaptive(0).to_glfw(), -1);
+    }
 }
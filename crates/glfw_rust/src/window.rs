@@ -1,6 +1,7 @@
 use std::ops::Deref;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::{Mutex, PoisonError};
 use std::thread::ThreadId;
 
 use tracing::error;
@@ -9,7 +10,7 @@ use crate::event::Event;
 use crate::ffi::*;
 use crate::*;
 #[derive(Debug, Default)]
-pub struct WindowConfig {
+pub struct WindowConfig<'a> {
     pub resizeable: Option<bool>,
     pub visible: Option<bool>,
     pub decorated: Option<bool>,
@@ -60,8 +61,12 @@ pub struct WindowConfig {
     pub x11_class_name: Option<String>,
     /// must be ascii
     pub x11_instance_name: Option<String>,
+    /// Where to place the window after creation. see [WindowPlacement].
+    ///
+    /// This is not a glfw hint, so [Self::set_hints] ignores it and [Window::new] resolves it.
+    pub placement: Option<WindowPlacement<'a>>,
 }
-impl WindowConfig {
+impl WindowConfig<'_> {
     /// <https://www.glfw.org/docs/latest/window_guide.html#window_hints>
     ///
     /// This contains a lot of options which are set by `glfwWindowHint`
@@ -75,6 +80,11 @@ impl WindowConfig {
     /// There's no guarantee that all of these options will be respected.
     /// Some are "hard constraints" like client api (opengl or non-opengl), while others are
     /// "soft constraints", where glfw will *try* to aim for the closest match (eg: opengl version).
+    ///
+    /// This always resets all hints to their defaults first (`glfwDefaultWindowHints`), so any hints
+    /// set for a previous window don't leak into the next one. If you want to check the config
+    /// for invalid combinations before creating a window, see [Self::validate].
+    #[doc(alias = "glfwDefaultWindowHints")]
     #[doc(alias = "glfwWindowHintString")]
     #[doc(alias = "glfwWindowHint")]
     pub fn set_hints(self, el: &EventLoop) -> Result<(), GlfwError> {
@@ -126,6 +136,7 @@ impl WindowConfig {
             wayland_app_id,
             x11_class_name,
             x11_instance_name,
+            placement: _,
         } = self;
         /// You can use it like this: `set_window_hint!(bool, name, hint)` for individual hints.
         /// For lots of hints, just do `set_window_hint!( (bool, name, hint), (string, name, hint), and so on)`
@@ -224,6 +235,268 @@ impl WindowConfig {
         }
     }
 }
+impl<'a> WindowConfig<'a> {
+    /// Returns a [WindowConfigBuilder] to fluently create a [WindowConfig].
+    ///
+    /// The builder starts from [WindowConfig::default] (i.e. glfw's default hints),
+    /// and [WindowConfigBuilder::build] validates the config before returning it.
+    ///
+    /// ```rust
+    /// # use glfw_rust::*;
+    /// let config = WindowConfig::builder()
+    ///     .context_version(3, 3)
+    ///     .opengl_profile(OpenGLProfile::Core)
+    ///     .x11_class_name("my_app")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> WindowConfigBuilder<'a> {
+        WindowConfigBuilder::default()
+    }
+    /// Checks that the config doesn't contain invalid combinations of hints.
+    ///
+    /// * The context version must be a valid version for the [ClientApi]
+    ///   (eg: OpenGL 3.4 or OpenGL ES 2.1 don't exist).
+    /// * [Self::context_version_minor] must not be set without [Self::context_version_major].
+    /// * [Self::opengl_profile] (other than [OpenGLProfile::Any]) requires OpenGL 3.2 or above.
+    /// * [Self::opengl_forward_compat] requires OpenGL 3.0 or above.
+    /// * [ClientApi::NoAPI] windows don't have a context, so none of the context related hints must be set.
+    /// * [Self::wayland_app_id], [Self::x11_class_name] and [Self::x11_instance_name] must be ascii.
+    /// * None of the strings must contain a null byte.
+    ///
+    /// Returns [ErrorCode::InvalidValue] with a description of the first problem found.
+    pub fn validate(&self) -> GlfwResult<()> {
+        fn invalid(description: String) -> GlfwResult<()> {
+            Err(GlfwError {
+                code: ErrorCode::InvalidValue,
+                description: format!("glfw-rust: invalid window config: {description}"),
+                context: None,
+            })
+        }
+        let client_api = self.client_api.unwrap_or_default();
+        if client_api == ClientApi::NoAPI {
+            let context_hints = [
+                ("context_creation_api", self.context_creation_api.is_some()),
+                (
+                    "context_version_major",
+                    self.context_version_major.is_some(),
+                ),
+                (
+                    "context_version_minor",
+                    self.context_version_minor.is_some(),
+                ),
+                ("context_robustness", self.context_robustness.is_some()),
+                (
+                    "context_release_behavior",
+                    self.context_release_behavior.is_some(),
+                ),
+                (
+                    "opengl_forward_compat",
+                    self.opengl_forward_compat.is_some(),
+                ),
+                ("opengl_context_debug", self.opengl_context_debug.is_some()),
+                ("opengl_profile", self.opengl_profile.is_some()),
+            ];
+            let set_hints: Vec<&str> = context_hints
+                .iter()
+                .filter_map(|(name, is_set)| is_set.then_some(*name))
+                .collect();
+            if !set_hints.is_empty() {
+                return invalid(format!(
+                    "client_api is NoAPI, but context hints {set_hints:?} are set"
+                ));
+            }
+        }
+        if self.context_version_minor.is_some() && self.context_version_major.is_none() {
+            return invalid("context_version_minor is set without context_version_major".into());
+        }
+        // glfw defaults to version 1.0
+        let major = self.context_version_major.unwrap_or(1);
+        let minor = self.context_version_minor.unwrap_or(0);
+        // same rules as glfw's `_glfwIsValidContextConfig`
+        match client_api {
+            ClientApi::OpenGL => {
+                if major < 1
+                    || minor < 0
+                    || (major == 1 && minor > 5)
+                    || (major == 2 && minor > 1)
+                    || (major == 3 && minor > 3)
+                {
+                    return invalid(format!("OpenGL {major}.{minor} doesn't exist"));
+                }
+                let profile = self.opengl_profile.unwrap_or_default();
+                if profile != OpenGLProfile::Any && (major < 3 || (major == 3 && minor < 2)) {
+                    return invalid(format!(
+                        "opengl_profile {profile:?} requires OpenGL 3.2 or above, but version is {major}.{minor}"
+                    ));
+                }
+                if self.opengl_forward_compat == Some(true) && major < 3 {
+                    return invalid(format!(
+                        "opengl_forward_compat requires OpenGL 3.0 or above, but version is {major}.{minor}"
+                    ));
+                }
+            }
+            ClientApi::OpenGLES => {
+                if major < 1 || minor < 0 || (major == 1 && minor > 1) || (major == 2 && minor > 0)
+                {
+                    return invalid(format!("OpenGL ES {major}.{minor} doesn't exist"));
+                }
+                if let Some(profile) = self.opengl_profile.filter(|p| *p != OpenGLProfile::Any) {
+                    return invalid(format!(
+                        "opengl_profile {profile:?} is only valid for OpenGL, not OpenGL ES"
+                    ));
+                }
+            }
+            ClientApi::NoAPI => {}
+        }
+        let strings = [
+            ("cocoa_frame_name", &self.cocoa_frame_name, false),
+            ("wayland_app_id", &self.wayland_app_id, true),
+            ("x11_class_name", &self.x11_class_name, true),
+            ("x11_instance_name", &self.x11_instance_name, true),
+        ];
+        for (name, value, must_be_ascii) in strings {
+            let Some(value) = value else {
+                continue;
+            };
+            if value.contains('\0') {
+                return invalid(format!("{name} {value:?} contains a null byte"));
+            }
+            if must_be_ascii && !value.is_ascii() {
+                return invalid(format!("{name} {value:?} must be ascii"));
+            }
+        }
+        Ok(())
+    }
+}
+/// A fluent builder for [WindowConfig]. Create it with [WindowConfig::builder].
+///
+/// Each method sets the field of the same name in [WindowConfig], so read the
+/// docs of [WindowConfig] for details.
+///
+/// Fields that are not set use glfw's default values, as [WindowConfig::set_hints]
+/// always resets all hints to defaults before setting the hints of the config.
+/// So, hints from a previous window will never leak into the next window.
+#[derive(Debug, Default)]
+pub struct WindowConfigBuilder<'a> {
+    config: WindowConfig<'a>,
+}
+/// generates setter methods for [WindowConfigBuilder].
+///
+/// `(bool, name)` generates `fn name(self, name: bool) -> Self` and so on.
+macro_rules! window_config_setters {
+    ($(($ty: ty, $name: ident),)*) => {
+        $(
+            #[doc = concat!("sets [WindowConfig::", stringify!($name), "]")]
+            pub fn $name(mut self, $name: $ty) -> Self {
+                self.config.$name = Some($name.into());
+                self
+            }
+        )*
+    };
+}
+impl<'a> WindowConfigBuilder<'a> {
+    window_config_setters!(
+        (bool, resizeable),
+        (bool, visible),
+        (bool, decorated),
+        (bool, focused),
+        (bool, auto_iconify),
+        (bool, floating),
+        (bool, maximized),
+        (bool, center_cursor),
+        (bool, transparent_framebuffer),
+        (bool, focus_on_show),
+        (bool, scale_to_monitor),
+        (bool, scale_framebuffer),
+        (bool, mouse_passthrough),
+        (i32, position_x),
+        (i32, position_y),
+        (i32, red_bits),
+        (i32, green_bits),
+        (i32, blue_bits),
+        (i32, alpha_bits),
+        (i32, depth_bits),
+        (i32, stencil_bits),
+        (i32, accum_red_bits),
+        (i32, accum_green_bits),
+        (i32, accum_blue_bits),
+        (i32, accum_alpha_bits),
+        (i32, aux_buffers),
+        (i32, samples),
+        (i32, refresh_rate),
+        (bool, stereo),
+        (bool, srgb_capable),
+        (bool, doublebuffer),
+        (ClientApi, client_api),
+        (ContextCreationApi, context_creation_api),
+        (i32, context_version_major),
+        (i32, context_version_minor),
+        (Robustness, context_robustness),
+        (ContextReleaseBehavior, context_release_behavior),
+        (bool, opengl_forward_compat),
+        (bool, opengl_context_debug),
+        (OpenGLProfile, opengl_profile),
+        (bool, win32_keyboard_menu),
+        (bool, win32_showdefault),
+        (&str, cocoa_frame_name),
+        (bool, cocoa_graphics_switching),
+        (&str, wayland_app_id),
+        (&str, x11_class_name),
+        (&str, x11_instance_name),
+        (WindowPlacement<'a>, placement),
+    );
+    /// sets both [WindowConfig::context_version_major] and [WindowConfig::context_version_minor]
+    pub fn context_version(self, major: i32, minor: i32) -> Self {
+        self.context_version_major(major)
+            .context_version_minor(minor)
+    }
+    /// Validates the config with [WindowConfig::validate] and returns it.
+    pub fn build(self) -> GlfwResult<WindowConfig<'a>> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+/// Where to place a new window. Set it with [WindowConfig::placement].
+///
+/// Glfw only lets us place windows at absolute positions (of the content area).
+/// So, [Window::new] creates the window hidden, resolves the placement using the monitor
+/// work area and the window frame size, moves the window and then shows it (unless
+/// [WindowConfig::visible] is false).
+///
+/// Wayland doesn't let clients position their windows, so [Window::new] will return
+/// [ErrorCode::FeatureUnavailable] if placement is set on wayland.
+#[derive(Clone, Copy)]
+pub enum WindowPlacement<'a> {
+    /// Center the window (including its frame) in the work area of the monitor.
+    Centered(MonitorId),
+    /// Center the window's content area over the content area of another window.
+    CenteredOnParent(&'a Window),
+    /// Place the window slightly below and to the right of the `parent_window` argument
+    /// of [Window::new]. If there's no parent window, we use the previous window
+    /// that was placed with [WindowPlacement::Cascade].
+    ///
+    /// If the window would go out of the primary monitor's work area, we start again
+    /// from the top-left corner of the work area.
+    Cascade,
+    /// The position, in screen coordinates, of the upper-left corner of the content area.
+    ///
+    /// This is the same as setting [WindowConfig::position_x] and [WindowConfig::position_y].
+    Absolute(i32, i32),
+}
+impl std::fmt::Debug for WindowPlacement<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Centered(monitor) => f.debug_tuple("Centered").field(monitor).finish(),
+            Self::CenteredOnParent(parent) => f
+                .debug_tuple("CenteredOnParent")
+                .field(&parent.id())
+                .finish(),
+            Self::Cascade => f.write_str("Cascade"),
+            Self::Absolute(x, y) => f.debug_tuple("Absolute").field(x).field(y).finish(),
+        }
+    }
+}
 
 /// This is data that is shared between [Window] and [WindowProxy]
 #[derive(Debug)]
@@ -257,37 +530,90 @@ pub(crate) struct WindowData {
     ///
     /// It never changes after the window is created.
     pub client_api: ClientApi,
-    /// Some functions like [WindowProxy::swap_buffers] have additional
+    /// Some functions like [CurrentGuard::swap_buffers] have additional
     /// restrictions with certain context creation apis like [ContextCreationApi::Egl].
     ///
     /// This also never changes after window is created.
     ///
     /// This is `None` if the window was created with [ClientApi::NoAPI]
     pub context_creation_api: Option<ContextCreationApi>,
+    /// The robustness strategy that glfw detected when creating the context.
+    ///
+    /// Cached here, as glfwGetWindowAttrib can only be called on the main thread.
+    pub context_robustness: Robustness,
+    /// Cached by [WindowProxy::context_info] the first time it is called.
+    pub context_info: OnceLock<ContextInfo>,
+    /// The filters passed to `glDebugMessageCallback` as user pointer by
+    /// [WindowProxy::enable_gl_debug_output], the last one is the active filter.
+    ///
+    /// Replaced filters are kept alive as long as the window, as asynchronous debug output
+    /// may still call the callback with them on a driver thread.
+    pub gl_debug_filters: Mutex<Vec<Arc<GlDebugFilter>>>,
+    /// The last interval set by [CurrentGuard::set_swap_interval], as glfw can't query it.
+    pub swap_interval: Mutex<Option<SwapInterval>>,
 }
+// SAFETY: `window` is the only field that isn't Send/Sync. The others are atomics, locks,
+// a `OnceLock` of owned data or `Copy` values that never change after creation.
+//
+// `window` is an opaque `GLFWwindow*` that we never dereference, it is only passed to glfw.
+// Sending it to another thread is fine as long as that thread
+// 1. only calls the glfw functions that glfw allows on any thread and
+// 2. never uses the handle after the window was destroyed.
+//
+// 1: The handle only leaves the main thread through [WindowProxy] (as [Window] is `!Send`),
+//    whose methods only call the any-thread functions (see "Thread safety" on [WindowProxy]).
+//    The main-thread only functions are only implemented on [Window].
+// 2: Every use of the handle off the main thread happens while holding the `current_thread`
+//    lock, after checking `is_alive` ([WindowProxy::with_checked] and [LOCAL_GL_CONTEXT]).
+//    The window is only destroyed on the main thread ([Window::drop],
+//    [destroy_released_windows] and `glfwTerminate`), after setting `is_alive` to false
+//    under the same lock. And it is never destroyed while `is_current` is set, as another
+//    thread may still use the context without the lock: the destruction is deferred instead.
+//
+// Dropping a `WindowData` doesn't destroy the window (it has no Drop impl), so the last
+// `Arc` may be dropped on any thread.
+unsafe impl Send for WindowData {}
+// SAFETY: `&WindowData` allows nothing beyond what Send allows: `window` is only read
+// (it never changes), and all the mutable state is behind atomics and locks.
+unsafe impl Sync for WindowData {}
 
 impl WindowData {
     /// We create a new window data from a window handle
     ///
+    /// Returns an error if the window attributes can't be queried.
+    ///
     /// # Safety
     /// The window handle must be valid
-    unsafe fn from_window(window: *mut GLFWwindow, el: &EventLoop) -> Self {
-        Self {
+    pub(crate) unsafe fn try_from_window(
+        window: *mut GLFWwindow,
+        el: &EventLoop,
+    ) -> GlfwResult<Self> {
+        let client_api = el.checked(|| glfwGetWindowAttrib(window, GLFW_CLIENT_API))?;
+        let client_api = client_api.try_into().map_err(|_| GlfwError {
+            code: ErrorCode::Crate(CrateError::InvalidWindowAttribute),
+            description: format!("glfw-rust: invalid client api {client_api}"),
+            context: None,
+        })?;
+        let context_creation_api = el
+            .checked(|| glfwGetWindowAttrib(window, GLFW_CONTEXT_CREATION_API))?
+            .try_into()
+            .ok();
+        let context_robustness = el
+            .checked(|| glfwGetWindowAttrib(window, GLFW_CONTEXT_ROBUSTNESS))?
+            .try_into()
+            .unwrap_or_default();
+        Ok(Self {
             window,
             current_thread: Mutex::new(std::thread::current().id()),
             is_current: AtomicBool::new(false),
             is_alive: AtomicBool::new(true),
-            client_api: el
-                .checked(|| glfwGetWindowAttrib(window, GLFW_CLIENT_API))
-                .expect("failed to query for client api")
-                .try_into()
-                .expect("invalid client api"),
-            context_creation_api: el
-                .checked(|| glfwGetWindowAttrib(window, GLFW_CONTEXT_CREATION_API))
-                .expect("failed to query for context creation api")
-                .try_into()
-                .ok(),
-        }
+            client_api,
+            context_creation_api,
+            context_robustness,
+            context_info: OnceLock::new(),
+            gl_debug_filters: Mutex::new(vec![]),
+            swap_interval: Mutex::new(None),
+        })
     }
 }
 /// This represents a native Glfw Window. All window-related methods that must be run on main-thread
@@ -314,27 +640,42 @@ pub struct Window {
     window: *mut GLFWwindow,
     data: Arc<WindowData>,
     weak_window: WindowProxy,
-    el: Rc<EventLoop>,
+    pub(crate) el: Rc<EventLoop>,
+    /// The cursor set by [Window::set_std_cursor], kept alive while the window uses it.
+    pub(crate) std_cursor: RefCell<Option<Rc<Cursor>>>,
 }
 impl Drop for Window {
     fn drop(&mut self) {
+        MAIN_THREAD_LOCAL_DATA.with(|data| data.cursor_positions.borrow_mut().remove(&self.id()));
+        self.stop_cursor_animation();
         clear_error();
         let current_ctx = LOCAL_GL_CONTEXT.with(|ctx| ctx.get_current());
         if current_ctx == Some(self.id()) {
             self.make_uncurrent();
             log_error();
         }
-        let guard = if let Ok(current_thread) = self.data.current_thread.try_lock() {
-            if self.data.is_current.load(Ordering::Acquire) {
-                error!("Window is being destroyed on current thread, but it is still current on an off-thread {current_thread:?}. This is UB.");
-            }
-            Some(current_thread)
-        } else {
-            error!("Window is being destroyed on main-thread, but someone else is still using it in off-thread or mutex is poisoned. This could be a bug.");
-            None
-        };
+        // off-threads only hold the lock for the duration of a single glfw call.
+        let current_thread = self
+            .data
+            .current_thread
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if self.data.is_current.load(Ordering::Acquire) {
+            // destroying a context that is current on another thread is UB, so we just hide the
+            // window and leave it to the event loop to destroy it once it is made uncurrent.
+            error!("Window is being dropped on main-thread, but it is still current on an off-thread {:?}. It will be destroyed after it is made uncurrent.", *current_thread);
+            std::mem::drop(current_thread);
+            unsafe { glfwHideWindow(self.window) };
+            log_error();
+            MAIN_THREAD_LOCAL_DATA.with(|data| {
+                data.deferred_windows
+                    .borrow_mut()
+                    .push((self.data.clone(), std::sync::Weak::new()));
+            });
+            return;
+        }
         self.data.is_alive.store(false, Ordering::Release);
-        std::mem::drop(guard);
+        std::mem::drop(current_thread);
         log_error();
         unsafe {
             glfwDestroyWindow(self.window);
@@ -375,7 +716,9 @@ impl Window {
     ///
     /// By default, newly created windows use the placement recommended by the window system.
     /// To create the window at a specific position, set the [WindowConfig::position_x] and
-    ///  [WindowConfig::position_y].
+    ///  [WindowConfig::position_y]. To center or cascade the window, set [WindowConfig::placement].
+    /// Placement is ignored for full screen windows and returns [ErrorCode::FeatureUnavailable]
+    /// on wayland.
     ///
     /// As long as at least one full screen window is not iconified,
     /// the screensaver is prohibited from starting.
@@ -384,20 +727,40 @@ impl Window {
     /// may be overridden by the window system on creation.
     /// Check the actual size([Window::get_size]) after creation.
     ///
+    /// Returns [CrateError::InteriorNul] if the title contains a null byte.
+    ///
     /// The swap interval is not set during window creation and the initial value
     /// may vary depending on driver settings and defaults.
     #[doc(alias = "glfwCreateWindow")]
     pub fn new(
         el: Rc<EventLoop>,
-        config: WindowConfig,
+        mut config: WindowConfig<'_>,
         width: u32,
         height: u32,
         title: &str,
         monitor: Option<MonitorId>,
         parent_window: Option<&Self>,
     ) -> GlfwResult<Self> {
+        // full screen windows cover the monitor, so there's nothing to place.
+        let placement = config.placement.take().filter(|_| monitor.is_none());
+        let visible = config.visible.unwrap_or(true);
+        if placement.is_some() {
+            if el.get_platform() == Platform::Wayland {
+                return Err(GlfwError {
+                    code: ErrorCode::FeatureUnavailable,
+                    description: "glfw-rust: wayland doesn't support window placement".to_string(),
+                    context: None,
+                });
+            }
+            // we will show the window after moving it to the right place.
+            config.visible = Some(false);
+        }
         config.set_hints(&el)?;
-        let title = CString::new(title).expect("window title contains null byte");
+        let title = CString::new(title).map_err(|_| GlfwError {
+            code: ErrorCode::Crate(CrateError::InteriorNul),
+            description: "glfw-rust: window title contains a null byte".to_string(),
+            context: None,
+        })?;
         if let Some(monitor) = monitor {
             if !el.is_monitor_alive(monitor) {
                 return Err(GlfwError::dead_monitor(monitor, "window creation"));
@@ -417,7 +780,13 @@ impl Window {
         std::mem::drop(title);
         assert!(!window.is_null());
         unsafe { set_window_callbacks(window, el.clone()) };
-        let data = Arc::new(unsafe { WindowData::from_window(window, &el) });
+        let data = match unsafe { WindowData::try_from_window(window, &el) } {
+            Ok(data) => Arc::new(data),
+            Err(error) => {
+                unsafe { glfwDestroyWindow(window) };
+                return Err(error);
+            }
+        };
         let proxy = el.new_proxy();
         let window = Window {
             window,
@@ -428,9 +797,67 @@ impl Window {
                 data,
                 proxy,
             },
+            std_cursor: RefCell::new(None),
         };
+        if let Some(placement) = placement {
+            window.place(placement, parent_window)?;
+            if visible {
+                window.show();
+            }
+        }
         Ok(window)
     }
+    /// Resolves the [WindowPlacement] into a position and moves the window there.
+    fn place(
+        &self,
+        placement: WindowPlacement<'_>,
+        parent_window: Option<&Self>,
+    ) -> GlfwResult<()> {
+        /// Minimum offset between cascaded windows, for windows without a title bar.
+        const CASCADE_MIN_OFFSET: i32 = 24;
+        let (width, height) = self.get_size();
+        let (left, top, right, bottom) = self.el.checked(|| self.get_frame_size())?;
+        let (left, top, right, bottom) = (left as i32, top as i32, right as i32, bottom as i32);
+        let (x, y) = match placement {
+            WindowPlacement::Absolute(x, y) => (x, y),
+            WindowPlacement::Centered(monitor) => {
+                let [area_x, area_y, area_width, area_height] =
+                    self.el.get_monitor_work_area(monitor)?;
+                (
+                    area_x + (area_width - (left + width + right)) / 2 + left,
+                    area_y + (area_height - (top + height + bottom)) / 2 + top,
+                )
+            }
+            WindowPlacement::CenteredOnParent(parent) => {
+                let (parent_x, parent_y) = self.el.checked(|| parent.get_pos())?;
+                let (parent_width, parent_height) = parent.get_size();
+                (
+                    parent_x + (parent_width - width) / 2,
+                    parent_y + (parent_height - height) / 2,
+                )
+            }
+            WindowPlacement::Cascade => {
+                let [area_x, area_y, area_width, area_height] = match self.el.get_primary_monitor()
+                {
+                    Some(monitor) => self.el.get_monitor_work_area(monitor)?,
+                    None => [0, 0, i32::MAX, i32::MAX],
+                };
+                let origin = (area_x + left, area_y + top);
+                let previous = match parent_window {
+                    Some(parent) => Some(self.el.checked(|| parent.get_pos())?),
+                    None => MAIN_THREAD_LOCAL_DATA.with(|data| data.cascade_position.get()),
+                };
+                let offset = top.max(CASCADE_MIN_OFFSET);
+                let (x, y) = previous.map_or(origin, |(x, y)| (x + offset, y + offset));
+                let fits = x + width + right <= area_x.saturating_add(area_width)
+                    && y + height + bottom <= area_y.saturating_add(area_height);
+                let position = if fits { (x, y) } else { origin };
+                MAIN_THREAD_LOCAL_DATA.with(|data| data.cascade_position.set(Some(position)));
+                position
+            }
+        };
+        self.el.checked(|| self.set_pos(x, y))
+    }
     /// This function returns the window title, encoded as UTF-8, of the specified window.
     /// This is the title set previously by [Self::new] or [Self::set_title].
     #[doc(alias = "glfwGetWindowTitle")]
@@ -451,14 +878,57 @@ impl Window {
         });
         drop(title);
     }
-    /*
-    #[doc = " @brief Sets the icon for the specified window.\n\n  This function sets the icon of the specified window.  If passed an array of\n  candidate images, those of or closest to the sizes desired by the system are\n  selected.  If no images are specified, the window reverts to its default\n  icon.\n\n  The pixels are 32-bit, little-endian, non-premultiplied RGBA, i.e. eight\n  bits per channel with the red channel first.  They are arranged canonically\n  as packed sequential rows, starting from the top-left corner.\n\n  The desired image sizes varies depending on platform and system settings.\n  The selected images will be rescaled as needed.  Good sizes include 16x16,\n  32x32 and 48x48.\n\n  @param[in] window The window whose icon to set.\n  @param[in] count The number of images in the specified array, or zero to\n  revert to the default window icon.\n  @param[in] images The images to create the icon from.  This is ignored if\n  count is zero.\n\n  @errors Possible errors include @ref GLFW_NOT_INITIALIZED, @ref\n  GLFW_INVALID_VALUE, @ref GLFW_PLATFORM_ERROR and @ref\n  GLFW_FEATURE_UNAVAILABLE (see remarks).\n\n  @pointer_lifetime The specified image data is copied before this function\n  returns.\n\n  @remark @macos Regular windows do not have icons on macOS.  This function\n  will emit @ref GLFW_FEATURE_UNAVAILABLE.  The dock icon will be the same as\n  the application bundle's icon.  For more information on bundles, see the\n  [Bundle Programming Guide][bundle-guide] in the Mac Developer Library.\n\n  [bundle-guide]: https://developer.apple.com/library/mac/documentation/CoreFoundation/Conceptual/CFBundles/\n\n  @remark @wayland There is no existing protocol to change an icon, the\n  window will thus inherit the one defined in the application's desktop file.\n  This function will emit @ref GLFW_FEATURE_UNAVAILABLE.\n\n  @thread_safety This function must only be called from the main thread.\n\n  @sa @ref window_icon\n\n  @since Added in version 3.2.\n\n  @ingroup window"]
-    pub fn glfwSetWindowIcon(
-        window: *mut GLFWwindow,
-        count: ::std::os::raw::c_int,
-        images: *const GLFWimage,
-    );
-    */
+    /// This function sets the icon of the specified window. If passed multiple
+    /// candidate images, those of or closest to the sizes desired by the system are
+    /// selected. If no images are specified, the window reverts to its default icon.
+    ///
+    /// Good sizes include 16x16, 32x32 and 48x48. see [decode_ico] to load images from `.ico` files.
+    ///
+    /// Regular windows don't have icons on macOS and wayland has no protocol to change them.
+    /// So, this returns [ErrorCode::FeatureUnavailable] on those platforms.
+    ///
+    /// Returns [CrateError::InvalidPixelBuffer] if the length of any image's pixels is not
+    /// `width * height * 4` or if its dimensions don't fit in an `i32`.
+    #[doc(alias = "glfwSetWindowIcon")]
+    pub fn set_icon(&self, images: &[Image]) -> GlfwResult<()> {
+        let invalid = |description: String| GlfwError {
+            code: ErrorCode::Crate(CrateError::InvalidPixelBuffer),
+            description,
+            context: None,
+        };
+        let images = images
+            .iter()
+            .map(|image| {
+                let Image {
+                    width,
+                    height,
+                    pixels,
+                } = image;
+                if *width as usize * *height as usize * 4 != pixels.len() {
+                    return Err(invalid(format!(
+                        "glfw-rust: icon of {width}x{height} needs {} bytes, but got {}",
+                        *width as usize * *height as usize * 4,
+                        pixels.len()
+                    )));
+                }
+                let (Ok(image_width), Ok(image_height)) =
+                    ((*width).try_into(), (*height).try_into())
+                else {
+                    return Err(invalid(format!(
+                        "glfw-rust: icon of {width}x{height} is too large"
+                    )));
+                };
+                Ok(GLFWimage {
+                    width: image_width,
+                    height: image_height,
+                    pixels: pixels.as_ptr().cast_mut(),
+                })
+            })
+            .collect::<GlfwResult<Vec<GLFWimage>>>()?;
+        self.el.checked(|| unsafe {
+            glfwSetWindowIcon(self.window, images.len() as i32, images.as_ptr());
+        })
+    }
     /// This function retrieves the position, in screen coordinates, of the upper-left corner
     /// of the content area of the specified window.
     ///
@@ -870,6 +1340,8 @@ impl Window {
     /// On some platforms, the set cursor may not be visible unless the window
     /// also has input focus.
     pub fn set_cursor(&self, cursor: Option<&Cursor>) {
+        self.stop_cursor_animation();
+        let _previous = self.std_cursor.take();
         unsafe {
             glfwSetCursor(
                 self.window,
@@ -1061,13 +1533,15 @@ impl Window {
     /// if it contains or is convertible to a UTF-8 encoded string.
     /// If the clipboard is empty or if its contents cannot be converted,
     /// Err is returned and a [ErrorCode::FormatUnavailable] error is generated.
+    ///
+    /// see [EventLoop::clipboard] for a clipboard api that doesn't need a window,
+    /// and returns `None` for an empty clipboard without generating an error.
     #[doc(alias = "glfwGetClipboardString")]
     pub fn get_clipboard_string(&self) -> GlfwResult<String> {
-        self.el.checked(|| unsafe {
-            let p = glfwGetClipboardString(self.window);
-            assert!(!p.is_null());
-            CStr::from_ptr(p).to_string_lossy().to_string()
-        })
+        self.el
+            .checked(|| unsafe { clipboard_text(glfwGetClipboardString(self.window)) })?
+            // glfw reports an error for an empty clipboard, but don't rely on that.
+            .ok_or_else(|| empty_clipboard("clipboard"))
     }
 }
 // pub fn should_close(&self) -> bool {
@@ -1122,21 +1596,57 @@ impl AsRef<EventLoop> for Window {
 /// you try to make it current on a different thread.
 ///
 /// A window must also never be destroyed while it is current on a thread. So,
-/// try to always make a window uncurrent before destroying it.
+/// try to always make a window uncurrent before dropping the [Window]. If it is current
+/// on the main thread, the [Window]'s drop will automatically make it uncurrent. If it is
+/// still current on another thread, the drop logs an error, hides the window and the event
+/// loop destroys it after it is made uncurrent on that thread.
 ///
 /// Generally speaking, we only panic in those cases. Most functions will prefer to
 /// ignore an error and you are supposed to rely on [EventLoopConfig::error_callback] for
 /// any other errors. Or use [get_error] to check for errors explicitly after a call.
 ///
-/// The one exception to the above rule is if window is current on main-thread.
-/// In that case, the [Window]'s drop will automatically make it uncurrent. This
-/// is just for the happy path convenience.
+/// # Thread safety
+/// Only the glfw functions that glfw documents as callable from any thread are used by the
+/// methods of this type (and of [CurrentGuard], [EventLoopProxy] and [GlLoader]):
+/// * [Self::set_should_close]/[Self::should_close]: `glfwSetWindowShouldClose`/`glfwWindowShouldClose`.
+///   glfw doesn't synchronize the flag, so we call them under the window's lock.
+/// * [Self::make_current], [Self::try_make_current], [Self::make_uncurrent] and
+///   [Self::make_current_scoped]: `glfwMakeContextCurrent`, under the window's lock.
+/// * [CurrentGuard::swap_buffers], [CurrentGuard::set_swap_interval],
+///   [CurrentGuard::get_proc_addr], [Self::extension_supported], [Self::context_info] and
+///   [Self::enable_gl_debug_output]: the context functions, which need the window to be
+///   current on the calling thread (checked with [Self::is_current_on_current_thread]).
+/// * [Self::create_window_surface] calls `glfwCreateWindowSurface`.
+/// * `read_color_buffer`/`read_depth_buffer` (`osmesa` feature): `glfwGetOSMesaColorBuffer`/`glfwGetOSMesaDepthBuffer`.
+/// * [Self::id], [Self::is_gl_window], [Self::is_current_somewhere],
+///   [Self::is_current_on_current_thread] and [Self::swap_interval] only read our own data.
+///
+/// All the functions that must be called on the main thread (window attributes, size,
+/// input etc..) are only implemented on [Window], which is `!Send`.
 #[derive(Debug, Clone)]
 pub struct WindowProxy {
     window: *mut GLFWwindow,
-    data: Arc<WindowData>,
+    pub(crate) data: Arc<WindowData>,
     proxy: EventLoopProxy,
 }
+// SAFETY: `window` is the same handle as `data.window` (see the Send impl of [WindowData]).
+// Every method only calls the glfw functions that may be called from any thread (listed in
+// the docs above), and only while holding the [WindowData::current_thread] lock after
+// checking [WindowData::is_alive]. [Window::drop] takes the same lock before destroying the
+// window, and defers the destruction while the window is current on another thread.
+unsafe impl Send for WindowProxy {}
+// SAFETY: see Send above. `&WindowProxy` gives no more access than a cloned proxy would.
+unsafe impl Sync for WindowProxy {}
+impl WindowProxy {
+    /// Creates a proxy for a window that is not owned by a [Window] (eg: [SharedContext]).
+    pub(crate) fn from_data(data: Arc<WindowData>, proxy: EventLoopProxy) -> Self {
+        Self {
+            window: data.window,
+            data,
+            proxy,
+        }
+    }
+}
 impl Deref for WindowProxy {
     type Target = EventLoopProxy;
     fn deref(&self) -> &Self::Target {
@@ -1149,7 +1659,7 @@ impl WindowProxy {
     /// 3. runs the `work` closure and returns value.
     ///
     /// This ensures that we only ever call glfw FFI functions when the window is alive.
-    fn with_checked<T>(&self, work: impl FnOnce() -> T) -> T {
+    pub(crate) fn with_checked<T>(&self, work: impl FnOnce() -> T) -> T {
         let guard = self.data.current_thread.lock().unwrap();
         assert!(self.data.is_alive.load(Ordering::Acquire));
         let result = work();
@@ -1159,7 +1669,7 @@ impl WindowProxy {
     /// 1. asserts that the window is current on the calling thread
     /// 2. calls [WindowProxy::with_checked] to run the `work` closure
     ///
-    /// useful for functions like [Self::swap_buffers] which only
+    /// useful for functions like [CurrentGuard::swap_buffers] which only
     /// work if the window is current.
     fn with_current_checked<T>(&self, work: impl FnOnce() -> T) -> T {
         assert!(self.is_current_on_current_thread());
@@ -1195,6 +1705,21 @@ impl WindowProxy {
         assert!(self.is_gl_window());
         LOCAL_GL_CONTEXT.with(|ctx| ctx.make_current(self.data.clone()))
     }
+    /// Like [Self::make_current], but returns an error instead of panicking.
+    ///
+    /// * [ErrorCode::NoWindowContext] if the window was not created with a gl context
+    /// * [CrateError::ContextCurrentElsewhere] if the window is current on a different thread
+    /// * [CrateError::WindowDead] if the window was destroyed
+    pub fn try_make_current(&self) -> GlfwResult<()> {
+        if !self.is_gl_window() {
+            return Err(GlfwError {
+                code: ErrorCode::NoWindowContext,
+                description: "glfw-rust: window was not created with a gl context".to_string(),
+                context: None,
+            });
+        }
+        LOCAL_GL_CONTEXT.with(|ctx| ctx.try_make_current(self.data.clone()))
+    }
     /// Makes this window uncurrent IF and ONLY IF it is current on the calling thread.
     /// otherwise, leaves the current context unchanged.
     ///
@@ -1216,42 +1741,21 @@ impl WindowProxy {
     pub fn is_current_on_current_thread(&self) -> bool {
         LOCAL_GL_CONTEXT.with(|ctx| ctx.get_current() == Some(self.id()))
     }
-    ///
-    /// This function swaps the front and back buffers of the specified window
-    /// when rendering with OpenGL or OpenGL ES. If the swap interval is greater
-    /// than zero, the GPU driver waits the specified number of screen updates
-    /// before swapping the buffers.
-    ///
-    /// see [Self::set_swap_interval].
+    /// see [CurrentGuard::swap_buffers].
     ///
     /// # Panics
     /// * **egl only**: if the window is not current on the calling thread, as egl requires being current for swap buffers to work
-    pub fn swap_buffers(&self) {
+    pub(crate) fn swap_buffers(&self) {
         if self.data.context_creation_api == Some(ContextCreationApi::Egl) {
             assert!(self.is_current_on_current_thread());
         }
         self.with_checked(|| unsafe { glfwSwapBuffers(self.window) })
     }
-    /// Returns opengl function pointer for `proc_name`.
-    ///
-    /// See [Self::get_proc_addr_cstr] if you want to use a `&CStr` for `proc_name`.
-    /// This function just internally calls that function, after creating a `CString` out
-    /// of `proc_name`.
+    /// see [CurrentGuard::get_proc_addr_cstr].
     ///
     /// # Panics
     /// * if the window is not current on the calling thread
-    /// * if proc-name contains a null-byte (which is not allowed for C strings)
-    pub fn get_proc_addr(&self, proc_name: &str) -> *mut std::ffi::c_void {
-        let proc_name = CString::new(proc_name).expect("proc_name contains null-byte");
-        self.get_proc_addr_cstr(&proc_name)
-    }
-    /// Returns opengl function pointer for `proc_name`.
-    ///
-    /// If you have a `&str` for `proc_name`, use [Self::get_proc_addr] instead.
-    ///
-    /// # Panics
-    /// * if the window is not current on the calling thread
-    pub fn get_proc_addr_cstr(&self, proc_name: &CStr) -> *mut std::ffi::c_void {
+    pub(crate) fn get_proc_addr_cstr(&self, proc_name: &CStr) -> *mut std::ffi::c_void {
         self.with_current_checked(|| unsafe {
             match glfwGetProcAddress(proc_name.as_ptr()) {
                 Some(ptr) => ptr as _,
@@ -1259,24 +1763,29 @@ impl WindowProxy {
             }
         })
     }
-    /// This function sets the swap interval for the current OpenGL or OpenGL ES context,
-    /// i.e. the number of screen updates to wait from the time
-    /// [Self::swap_buffers] was called before swapping the buffers
-    /// and returning. This is sometimes called vertical synchronization,
-    /// vertical retrace synchronization or just vsync.
-    ///
-    /// A context that supports either of the WGL_EXT_swap_control_tear and
-    /// GLX_EXT_swap_control_tear extensions also accepts negative swap intervals,
-    /// which allows the driver to swap immediately even if a frame arrives
-    /// a little bit late. You can check for these extensions with
-    /// [Self::extension_supported].
-    ///
-    /// This function does not apply to Vulkan. If you are rendering with Vulkan, see the present mode of your swapchain instead.
+    /// see [CurrentGuard::set_swap_interval].
     ///
     /// # Panics
     /// * if the window is not current on the calling thread
-    pub fn set_swap_interval(&self, interval: i32) {
-        self.with_current_checked(|| unsafe { glfwSwapInterval(interval) })
+    pub(crate) fn set_swap_interval(&self, interval: impl Into<SwapInterval>) -> SwapInterval {
+        let mut interval = interval.into();
+        if let SwapInterval::Adaptive(n) = interval {
+            if !(self.extension_supported_cstr(c"WGL_EXT_swap_control_tear")
+                || self.extension_supported_cstr(c"GLX_EXT_swap_control_tear"))
+            {
+                interval = SwapInterval::Vsync(n.max(1));
+            }
+        }
+        self.with_current_checked(|| unsafe { glfwSwapInterval(interval.to_glfw()) });
+        *self.data.swap_interval.lock().unwrap() = Some(interval);
+        interval
+    }
+    /// Returns the last interval set with [CurrentGuard::set_swap_interval].
+    ///
+    /// Glfw can't query the swap interval, so this is None if it was never set
+    /// (the default interval depends on the driver).
+    pub fn swap_interval(&self) -> Option<SwapInterval> {
+        *self.data.swap_interval.lock().unwrap()
     }
     /// This function returns whether the specified API extension(@ref context_glext) is supported by the current OpenGL or OpenGL ES context. It searches both for client API extension and context creation API extensions.
     ///
@@ -1290,7 +1799,7 @@ impl WindowProxy {
     ///
     /// If you have a `&CStr` for `extension`, use [Self::extension_supported_cstr] instead.
     /// Internally, this calls that function too.
-    /// 
+    ///
     /// # Panics
     /// * if the window is not current on the calling thread
     /// * if `extension` contains a null-byte, which is not allowed for C strings
@@ -1336,6 +1845,9 @@ impl WindowProxy {
     ///
     /// # Panics
     /// * If the client api is not [ClientApi::NoAPI]
+    ///
+    /// # Safety
+    /// `instance` must be a valid vulkan instance and `allocator` (if provided) must be valid.
     #[doc(alias = "glfwCreateWindowSurface")]
     pub unsafe fn create_window_surface(
         &self,
@@ -1470,6 +1982,9 @@ unsafe extern "C" fn refresh_cb(window: *mut GLFWwindow) {
 }
 /// pushes [Event::Focus] event to the thread-local event queue
 unsafe extern "C" fn focus_cb(window: *mut GLFWwindow, focused: i32) {
+    if focused != GLFW_TRUE {
+        release_pointer_lock(WindowId(window), None);
+    }
     push_event_to_thread_local(Event::Focus {
         window: WindowId(window),
         focused: focused == GLFW_TRUE,
@@ -1572,11 +2087,30 @@ unsafe extern "C" fn mouse_button_cb(window: *mut GLFWwindow, button: i32, actio
 }
 /// pushes [Event::CursorPos] event to the thread-local event queue
 unsafe extern "C" fn cursor_pos_cb(window: *mut GLFWwindow, x: f64, y: f64) {
+    let motion = MAIN_THREAD_LOCAL_DATA.with(|data| {
+        data.cursor_positions
+            .borrow_mut()
+            .insert(WindowId(window), (x, y));
+        data.pointer_locks
+            .borrow_mut()
+            .get_mut(&WindowId(window))
+            .map(|lock| {
+                let (last_x, last_y) = std::mem::replace(&mut lock.last_pos, (x, y));
+                (x - last_x, y - last_y)
+            })
+    });
     push_event_to_thread_local(Event::CursorPos {
         window: WindowId(window),
         x,
         y,
     });
+    if let Some((dx, dy)) = motion {
+        push_event_to_thread_local(Event::MouseMotion {
+            window: WindowId(window),
+            dx,
+            dy,
+        });
+    }
 }
 /// pushes [Event::CursorEnter] event to the thread-local event queue
 unsafe extern "C" fn cursor_enter_cb(window: *mut GLFWwindow, entered: i32) {
@@ -1604,20 +2138,372 @@ unsafe extern "C" fn drop_cb(
         .iter()
         .map(|&s| {
             let cstr = CStr::from_ptr(s);
-            match cstr.to_str() {
-                Ok(s) => s.to_string(),
-                Err(e) => {
-                    error!(
-                        "path is not utf-8: {}. using lossy conversion: {:?}",
-                        e, cstr
-                    );
-                    cstr.to_string_lossy().into_owned()
-                }
+            // glfw gives us the raw bytes on unix, which need not be utf-8
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                PathBuf::from(std::ffi::OsStr::from_bytes(cstr.to_bytes()))
+            }
+            // on windows, glfw converts the paths from utf-16 to utf-8
+            #[cfg(not(unix))]
+            {
+                PathBuf::from(cstr.to_string_lossy().into_owned())
             }
         })
         .collect();
+    let last_position = MAIN_THREAD_LOCAL_DATA.with(|data| {
+        data.cursor_positions
+            .borrow()
+            .get(&WindowId(window))
+            .copied()
+    });
+    // no cursor position event yet, so just ask glfw.
+    let (x, y) = last_position.unwrap_or_else(|| {
+        let (mut x, mut y) = (0.0, 0.0);
+        glfwGetCursorPos(window, &mut x, &mut y);
+        (x, y)
+    });
     push_event_to_thread_local(Event::Drop {
         window: WindowId(window),
+        x,
+        y,
         paths,
     });
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    /// Asserts that `config` is rejected with a description containing `problem`.
+    #[track_caller]
+    fn assert_invalid(config: WindowConfig<'_>, problem: &str) {
+        let error = config.validate().expect_err(problem);
+        assert_eq!(error.code, ErrorCode::InvalidValue);
+        assert!(
+            error.description.contains(problem),
+            "{:?} doesn't mention {problem:?}",
+            error.description
+        );
+    }
+    fn gl(major: i32, minor: i32) -> WindowConfig<'static> {
+        WindowConfig {
+            client_api: Some(ClientApi::OpenGL),
+            context_version_major: Some(major),
+            context_version_minor: Some(minor),
+            ..Default::default()
+        }
+    }
+    fn gles(major: i32, minor: i32) -> WindowConfig<'static> {
+        WindowConfig {
+            client_api: Some(ClientApi::OpenGLES),
+            ..gl(major, minor)
+        }
+    }
+    #[test]
+    fn validate_context_hints_of_no_api() {
+        let no_api = || WindowConfig {
+            client_api: Some(ClientApi::NoAPI),
+            ..Default::default()
+        };
+        no_api().validate().unwrap();
+        let context_hints: [(&str, WindowConfig); 8] = [
+            (
+                "context_creation_api",
+                WindowConfig {
+                    context_creation_api: Some(ContextCreationApi::Egl),
+                    ..no_api()
+                },
+            ),
+            (
+                "context_version_major",
+                WindowConfig {
+                    context_version_major: Some(3),
+                    ..no_api()
+                },
+            ),
+            (
+                "context_version_minor",
+                WindowConfig {
+                    context_version_minor: Some(3),
+                    ..no_api()
+                },
+            ),
+            (
+                "context_robustness",
+                WindowConfig {
+                    context_robustness: Some(Robustness::No),
+                    ..no_api()
+                },
+            ),
+            (
+                "context_release_behavior",
+                WindowConfig {
+                    context_release_behavior: Some(ContextReleaseBehavior::Flush),
+                    ..no_api()
+                },
+            ),
+            (
+                "opengl_forward_compat",
+                WindowConfig {
+                    opengl_forward_compat: Some(false),
+                    ..no_api()
+                },
+            ),
+            (
+                "opengl_context_debug",
+                WindowConfig {
+                    opengl_context_debug: Some(true),
+                    ..no_api()
+                },
+            ),
+            (
+                "opengl_profile",
+                WindowConfig {
+                    opengl_profile: Some(OpenGLProfile::Any),
+                    ..no_api()
+                },
+            ),
+        ];
+        for (hint, config) in context_hints {
+            assert_invalid(config, hint);
+        }
+    }
+    #[test]
+    fn validate_context_versions() {
+        WindowConfig::default().validate().unwrap();
+        for (major, minor) in [(1, 0), (1, 5), (2, 1), (3, 3), (4, 6)] {
+            gl(major, minor).validate().unwrap();
+        }
+        for (major, minor) in [(0, 9), (1, 6), (2, 2), (3, 4), (3, -1)] {
+            assert_invalid(gl(major, minor), "doesn't exist");
+        }
+        for (major, minor) in [(1, 0), (1, 1), (2, 0), (3, 2)] {
+            gles(major, minor).validate().unwrap();
+        }
+        for (major, minor) in [(0, 0), (1, 2), (2, 1)] {
+            assert_invalid(gles(major, minor), "OpenGL ES");
+        }
+        assert_invalid(
+            WindowConfig {
+                context_version_minor: Some(3),
+                ..Default::default()
+            },
+            "context_version_minor is set without context_version_major",
+        );
+    }
+    #[test]
+    fn validate_profile_and_forward_compat() {
+        for profile in [OpenGLProfile::Core, OpenGLProfile::Compatibility] {
+            assert_invalid(
+                WindowConfig {
+                    opengl_profile: Some(profile),
+                    ..gl(3, 1)
+                },
+                "requires OpenGL 3.2",
+            );
+            // the default version is 1.0
+            assert_invalid(
+                WindowConfig {
+                    opengl_profile: Some(profile),
+                    ..Default::default()
+                },
+                "requires OpenGL 3.2",
+            );
+            WindowConfig {
+                opengl_profile: Some(profile),
+                ..gl(3, 2)
+            }
+            .validate()
+            .unwrap();
+            assert_invalid(
+                WindowConfig {
+                    opengl_profile: Some(profile),
+                    ..gles(3, 2)
+                },
+                "not OpenGL ES",
+            );
+        }
+        WindowConfig {
+            opengl_profile: Some(OpenGLProfile::Any),
+            ..gles(3, 2)
+        }
+        .validate()
+        .unwrap();
+
+        assert_invalid(
+            WindowConfig {
+                opengl_forward_compat: Some(true),
+                ..gl(2, 1)
+            },
+            "requires OpenGL 3.0",
+        );
+        for config in [
+            WindowConfig {
+                opengl_forward_compat: Some(false),
+                ..gl(2, 1)
+            },
+            WindowConfig {
+                opengl_forward_compat: Some(true),
+                ..gl(3, 0)
+            },
+        ] {
+            config.validate().unwrap();
+        }
+    }
+    #[test]
+    fn validate_strings() {
+        let names = |name: &str| WindowConfig {
+            cocoa_frame_name: Some(name.to_string()),
+            wayland_app_id: Some(name.to_string()),
+            x11_class_name: Some(name.to_string()),
+            x11_instance_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        names("my_app").validate().unwrap();
+        assert_invalid(names("my\0app"), "cocoa_frame_name");
+        // only the cocoa frame name may be unicode
+        WindowConfig {
+            cocoa_frame_name: Some("アプリ".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .unwrap();
+        // sets one of the ascii-only names to `value`
+        let with = |name: &str, value: &str| {
+            let mut config = names("my_app");
+            let field = match name {
+                "wayland_app_id" => &mut config.wayland_app_id,
+                "x11_class_name" => &mut config.x11_class_name,
+                _ => &mut config.x11_instance_name,
+            };
+            *field = Some(value.to_string());
+            config
+        };
+        for name in ["wayland_app_id", "x11_class_name", "x11_instance_name"] {
+            assert_invalid(
+                with(name, "アプリ"),
+                &format!("{name} \"アプリ\" must be ascii"),
+            );
+            assert_invalid(
+                with(name, "my\0app"),
+                &format!("{name} \"my\\0app\" contains a null byte"),
+            );
+        }
+    }
+    #[test]
+    fn builder_round_trip() {
+        let built = WindowConfig::builder()
+            .resizeable(false)
+            .visible(false)
+            .decorated(false)
+            .focused(false)
+            .auto_iconify(false)
+            .floating(true)
+            .maximized(true)
+            .center_cursor(false)
+            .transparent_framebuffer(true)
+            .focus_on_show(false)
+            .scale_to_monitor(true)
+            .scale_framebuffer(false)
+            .mouse_passthrough(true)
+            .position_x(10)
+            .position_y(20)
+            .red_bits(5)
+            .green_bits(6)
+            .blue_bits(5)
+            .alpha_bits(0)
+            .depth_bits(16)
+            .stencil_bits(0)
+            .accum_red_bits(1)
+            .accum_green_bits(2)
+            .accum_blue_bits(3)
+            .accum_alpha_bits(4)
+            .aux_buffers(2)
+            .samples(4)
+            .refresh_rate(60)
+            .stereo(true)
+            .srgb_capable(true)
+            .doublebuffer(false)
+            .client_api(ClientApi::OpenGL)
+            .context_creation_api(ContextCreationApi::Egl)
+            .context_version(4, 6)
+            .context_robustness(Robustness::LoseContextOnReset)
+            .context_release_behavior(ContextReleaseBehavior::None)
+            .opengl_forward_compat(true)
+            .opengl_context_debug(true)
+            .opengl_profile(OpenGLProfile::Core)
+            .win32_keyboard_menu(true)
+            .win32_showdefault(true)
+            .cocoa_frame_name("frame")
+            .cocoa_graphics_switching(true)
+            .wayland_app_id("app_id")
+            .x11_class_name("class")
+            .x11_instance_name("instance")
+            .placement(WindowPlacement::Absolute(30, 40))
+            .build()
+            .unwrap();
+        let expected = WindowConfig {
+            resizeable: Some(false),
+            visible: Some(false),
+            decorated: Some(false),
+            focused: Some(false),
+            auto_iconify: Some(false),
+            floating: Some(true),
+            maximized: Some(true),
+            center_cursor: Some(false),
+            transparent_framebuffer: Some(true),
+            focus_on_show: Some(false),
+            scale_to_monitor: Some(true),
+            scale_framebuffer: Some(false),
+            mouse_passthrough: Some(true),
+            position_x: Some(10),
+            position_y: Some(20),
+            red_bits: Some(5),
+            green_bits: Some(6),
+            blue_bits: Some(5),
+            alpha_bits: Some(0),
+            depth_bits: Some(16),
+            stencil_bits: Some(0),
+            accum_red_bits: Some(1),
+            accum_green_bits: Some(2),
+            accum_blue_bits: Some(3),
+            accum_alpha_bits: Some(4),
+            aux_buffers: Some(2),
+            samples: Some(4),
+            refresh_rate: Some(60),
+            stereo: Some(true),
+            srgb_capable: Some(true),
+            doublebuffer: Some(false),
+            client_api: Some(ClientApi::OpenGL),
+            context_creation_api: Some(ContextCreationApi::Egl),
+            context_version_major: Some(4),
+            context_version_minor: Some(6),
+            context_robustness: Some(Robustness::LoseContextOnReset),
+            context_release_behavior: Some(ContextReleaseBehavior::None),
+            opengl_forward_compat: Some(true),
+            opengl_context_debug: Some(true),
+            opengl_profile: Some(OpenGLProfile::Core),
+            win32_keyboard_menu: Some(true),
+            win32_showdefault: Some(true),
+            cocoa_frame_name: Some("frame".to_string()),
+            cocoa_graphics_switching: Some(true),
+            wayland_app_id: Some("app_id".to_string()),
+            x11_class_name: Some("class".to_string()),
+            x11_instance_name: Some("instance".to_string()),
+            placement: Some(WindowPlacement::Absolute(30, 40)),
+        };
+        // WindowConfig isn't PartialEq (WindowPlacement may borrow a window), so compare the Debug output.
+        assert_eq!(format!("{built:?}"), format!("{expected:?}"));
+        assert_eq!(
+            format!("{:?}", WindowConfig::builder().build().unwrap()),
+            format!("{:?}", WindowConfig::default())
+        );
+        // build validates the config
+        let error = WindowConfig::builder()
+            .client_api(ClientApi::NoAPI)
+            .context_version(3, 3)
+            .build()
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidValue);
+    }
+}
@@ -0,0 +1,367 @@
+//! A parser for Xcursor files, the cursor format of X11/Wayland cursor themes.
+//!
+//! The format is documented in `man 3 Xcursor`. A file contains images of one or more
+//! nominal sizes (eg: 24, 32, 48), and each size may have multiple images, which are the
+//! frames of an animation.
+use std::path::{Path, PathBuf};
+
+use crate::*;
+
+/// `XCURSOR_IMAGE_TYPE` chunk type
+const IMAGE_CHUNK: u32 = 0xfffd_0002;
+
+/// A cursor loaded from a file, which may be animated. see [Cursor::from_xcursor_bytes]
+#[derive(Debug)]
+pub enum LoadedCursor {
+    /// The file had a single image
+    Static(Cursor),
+    /// The file had multiple images (frames) for the chosen size
+    Animated(Rc<AnimatedCursor>),
+}
+impl LoadedCursor {
+    /// Sets the cursor for `window` with [Window::set_cursor] or [Window::set_animated_cursor].
+    pub fn set_for(&self, window: &Window) {
+        match self {
+            LoadedCursor::Static(cursor) => window.set_cursor(Some(cursor)),
+            LoadedCursor::Animated(cursor) => window.set_animated_cursor(cursor.clone()),
+        }
+    }
+}
+/// A single image of an Xcursor file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct XcursorFrame {
+    pub image: Image,
+    pub x_hot: u32,
+    pub y_hot: u32,
+    /// how long the frame is shown (in milliseconds) in animated cursors
+    pub delay: u32,
+}
+fn invalid_xcursor(description: &str) -> GlfwError {
+    GlfwError {
+        code: ErrorCode::InvalidValue,
+        description: format!("glfw-rust: invalid xcursor: {description}"),
+        context: None,
+    }
+}
+/// Parses the frames of the size closest to `nominal_size` from an Xcursor file.
+///
+/// All offsets and sizes come from the file, so they are bounds-checked with [Reader].
+pub(crate) fn parse_xcursor(bytes: &[u8], nominal_size: u32) -> GlfwResult<Vec<XcursorFrame>> {
+    if bytes.get(0..4) != Some(b"Xcur") {
+        return Err(invalid_xcursor("missing Xcur magic"));
+    }
+    let reader = Reader::new(bytes, invalid_xcursor);
+    let header_size = reader.u32(4)? as usize;
+    let toc_count = reader.u32(12)? as usize;
+    // each entry is 12 bytes, so this also rejects counts larger than the file
+    let toc = reader.bytes(
+        header_size,
+        toc_count
+            .checked_mul(12)
+            .ok_or_else(|| invalid_xcursor("table of contents is too large"))?,
+    )?;
+    let toc = Reader::new(toc, invalid_xcursor);
+    // (nominal size, position) of all the image chunks
+    let mut images = Vec::new();
+    for entry in (0..toc_count).map(|index| index * 12) {
+        if toc.u32(entry)? == IMAGE_CHUNK {
+            images.push((toc.u32(entry + 4)?, toc.u32(entry + 8)? as usize));
+        }
+    }
+    let best_size = images
+        .iter()
+        .map(|&(size, _)| size)
+        .min_by_key(|&size| size.abs_diff(nominal_size))
+        .ok_or_else(|| invalid_xcursor("no images"))?;
+    let mut frames = Vec::new();
+    // the pixels of all the frames can't be more than the file, unless chunks overlap
+    let mut total_len = 0usize;
+    for &(_, position) in images.iter().filter(|(size, _)| *size == best_size) {
+        let chunk = reader.at(position)?;
+        let chunk_header_size = chunk.u32(0)? as usize;
+        if chunk.u32(4)? != IMAGE_CHUNK {
+            return Err(invalid_xcursor("toc points to a non-image chunk"));
+        }
+        if chunk_header_size < 36 {
+            return Err(invalid_xcursor("image chunk header is too small"));
+        }
+        let width = chunk.u32(16)?;
+        let height = chunk.u32(20)?;
+        let x_hot = chunk.u32(24)?;
+        let y_hot = chunk.u32(28)?;
+        let delay = chunk.u32(32)?;
+        // the spec limits images to 0x7fff pixels in each dimension
+        if width == 0 || height == 0 || width > 0x7fff || height > 0x7fff {
+            return Err(invalid_xcursor("invalid image size"));
+        }
+        if x_hot > width || y_hot > height {
+            return Err(invalid_xcursor("hotspot outside of image"));
+        }
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| invalid_xcursor("invalid image size"))?;
+        total_len = total_len
+            .checked_add(len)
+            .filter(|&total| total <= bytes.len())
+            .ok_or_else(|| invalid_xcursor("image data is larger than the file"))?;
+        let argb = chunk.bytes(chunk_header_size, len)?;
+        // pixels are premultiplied ARGB in little-endian u32s, so bytes are B G R A
+        let mut pixels = Vec::with_capacity(len);
+        for pixel in argb.chunks_exact(4) {
+            let (b, g, r, a) = (pixel[0], pixel[1], pixel[2], pixel[3]);
+            let unpremultiply = |c: u8| {
+                if a == 0 {
+                    0
+                } else {
+                    (c as u32 * 255 / a as u32).min(255) as u8
+                }
+            };
+            pixels.extend_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), a]);
+        }
+        frames.push(XcursorFrame {
+            image: Image {
+                width,
+                height,
+                pixels,
+            },
+            x_hot,
+            y_hot,
+            delay,
+        });
+    }
+    Ok(frames)
+}
+/// Creates a glfw cursor from a parsed frame.
+fn cursor_from_frame(el: &Rc<EventLoop>, frame: &XcursorFrame) -> GlfwResult<Cursor> {
+    Cursor::try_new_from_pixels(
+        el.clone(),
+        frame.image.width,
+        frame.image.height,
+        &frame.image.pixels,
+        frame.x_hot as i32,
+        frame.y_hot as i32,
+    )
+}
+impl Cursor {
+    /// Loads a cursor from the contents of an Xcursor file (the format of X11/Wayland cursor themes).
+    ///
+    /// The file may contain images for multiple sizes. We use the images of the size closest
+    /// to `nominal_size` (eg: 24 or 32). If there are multiple images for that size, they
+    /// are the frames of an animation and we return an [AnimatedCursor].
+    ///
+    /// Returns [ErrorCode::InvalidValue] if the file is malformed.
+    pub fn from_xcursor_bytes(
+        el: Rc<EventLoop>,
+        bytes: &[u8],
+        nominal_size: u32,
+    ) -> GlfwResult<LoadedCursor> {
+        let frames = parse_xcursor(bytes, nominal_size)?;
+        if let [frame] = frames.as_slice() {
+            return cursor_from_frame(&el, frame).map(LoadedCursor::Static);
+        }
+        let frames = frames
+            .iter()
+            .map(|frame| {
+                // a zero delay would be a busy-loop, so clamp it to 1ms
+                let duration = frame.delay.max(1) as f64 / 1000.0;
+                Ok((cursor_from_frame(&el, frame)?, duration))
+            })
+            .collect::<GlfwResult<Vec<_>>>()?;
+        Ok(LoadedCursor::Animated(Rc::new(AnimatedCursor::try_new(
+            frames,
+        )?)))
+    }
+    /// Loads the cursor `name` (eg: "left_ptr" or "wait") from the cursor theme `theme`.
+    ///
+    /// If `theme` is None, uses `XCURSOR_THEME` or "default".
+    /// The file is searched with [find_xcursor_file]. `nominal_size` is the same as in
+    /// [Cursor::from_xcursor_bytes], use `XCURSOR_SIZE` if you want to follow the user's setting.
+    ///
+    /// Returns [ErrorCode::CursorUnavailable] if the cursor is not found in the theme.
+    pub fn from_xcursor_theme(
+        el: Rc<EventLoop>,
+        theme: Option<&str>,
+        name: &str,
+        nominal_size: u32,
+    ) -> GlfwResult<LoadedCursor> {
+        let theme = theme
+            .map(str::to_string)
+            .or_else(|| std::env::var("XCURSOR_THEME").ok())
+            .unwrap_or_else(|| "default".to_string());
+        let path = find_xcursor_file(&theme, name).ok_or_else(|| GlfwError {
+            code: ErrorCode::CursorUnavailable,
+            description: format!("glfw-rust: cursor {name} not found in theme {theme}"),
+            context: None,
+        })?;
+        let bytes = std::fs::read(&path).map_err(|e| GlfwError {
+            code: ErrorCode::PlatformError,
+            description: format!("glfw-rust: failed to read {}: {e}", path.display()),
+            context: None,
+        })?;
+        Self::from_xcursor_bytes(el, &bytes, nominal_size)
+    }
+}
+/// The directories in which cursor themes are searched.
+///
+/// `XCURSOR_PATH` (colon separated) if set, otherwise the default of libXcursor:
+/// `~/.local/share/icons`, `~/.icons`, `/usr/share/icons` and `/usr/share/pixmaps`.
+pub fn xcursor_search_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let expand = |dir: &str| -> Option<PathBuf> {
+        match dir.strip_prefix("~/") {
+            Some(rest) => home.as_ref().map(|home| home.join(rest)),
+            None => Some(PathBuf::from(dir)),
+        }
+    };
+    let path = std::env::var("XCURSOR_PATH").unwrap_or_else(|_| {
+        "~/.local/share/icons:~/.icons:/usr/share/icons:/usr/share/pixmaps".to_string()
+    });
+    path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .filter_map(expand)
+        .collect()
+}
+/// Finds the Xcursor file of the cursor `name` in `theme`, searching [xcursor_search_dirs].
+///
+/// Like libXcursor, if the theme doesn't have the cursor, the themes listed in the
+/// `Inherits` key of its `index.theme` are searched too.
+pub fn find_xcursor_file(theme: &str, name: &str) -> Option<PathBuf> {
+    let dirs = xcursor_search_dirs();
+    let mut themes = vec![theme.to_string()];
+    let mut searched = Vec::new();
+    // themes can inherit each other in cycles, so we remember what we searched
+    while let Some(theme) = themes.pop() {
+        if searched.contains(&theme) || searched.len() > 32 {
+            continue;
+        }
+        for dir in &dirs {
+            let path = dir.join(&theme).join("cursors").join(name);
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+        // search the inherited themes in order, so push them in reverse
+        let mut inherits: Vec<String> = dirs
+            .iter()
+            .flat_map(|dir| read_inherits(&dir.join(&theme).join("index.theme")))
+            .collect();
+        inherits.reverse();
+        themes.extend(inherits);
+        searched.push(theme);
+    }
+    None
+}
+/// Reads the `Inherits=a,b` line of an `index.theme` file.
+fn read_inherits(index_theme: &Path) -> Vec<String> {
+    let Ok(contents) = std::fs::read_to_string(index_theme) else {
+        return vec![];
+    };
+    contents
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Inherits"))
+        .filter_map(|rest| rest.trim_start().strip_prefix('='))
+        .flat_map(|themes| themes.split([',', ';']))
+        .map(|theme| theme.trim().to_string())
+        .filter(|theme| !theme.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    /// builds an xcursor file with one image chunk per (nominal size, width, delay)
+    fn build_xcursor(images: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let push = |bytes: &mut Vec<u8>, value: u32| bytes.extend_from_slice(&value.to_le_bytes());
+        bytes.extend_from_slice(b"Xcur");
+        push(&mut bytes, 16);
+        push(&mut bytes, 0x1_0000);
+        push(&mut bytes, images.len() as u32);
+        let mut position = 16 + images.len() as u32 * 12;
+        for &(size, width, _) in images {
+            push(&mut bytes, IMAGE_CHUNK);
+            push(&mut bytes, size);
+            push(&mut bytes, position);
+            position += 36 + width * width * 4;
+        }
+        for &(size, width, delay) in images {
+            for value in [36, IMAGE_CHUNK, size, 1, width, width, 1, 1, delay] {
+                push(&mut bytes, value);
+            }
+            for _ in 0..width * width {
+                // premultiplied half transparent red: a = 0x80, r = 0x40
+                push(&mut bytes, 0x8040_0000);
+            }
+        }
+        bytes
+    }
+    #[test]
+    fn picks_nearest_size_and_frames() {
+        let bytes = build_xcursor(&[(24, 2, 0), (32, 3, 50), (32, 3, 70)]);
+        let frames = parse_xcursor(&bytes, 30).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].image.width, 3);
+        assert_eq!((frames[0].x_hot, frames[0].y_hot), (1, 1));
+        assert_eq!(frames[1].delay, 70);
+        // un-premultiplied
+        assert_eq!(&frames[0].image.pixels[0..4], &[0x7f, 0, 0, 0x80]);
+
+        let frames = parse_xcursor(&bytes, 16).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].image.width, 2);
+    }
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(parse_xcursor(b"nope", 24).is_err());
+        let bytes = build_xcursor(&[(24, 2, 0)]);
+        assert!(parse_xcursor(&bytes[..bytes.len() - 1], 24).is_err());
+    }
+    /// overwrites the u32 at `offset`
+    fn patch(bytes: &[u8], offset: usize, value: u32) -> Vec<u8> {
+        let mut bytes = bytes.to_vec();
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        bytes
+    }
+    fn assert_invalid(bytes: &[u8], description: &str) {
+        let error = parse_xcursor(bytes, 24).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidValue);
+        assert!(
+            error.description.ends_with(description),
+            "{}",
+            error.description
+        );
+    }
+    #[test]
+    fn rejects_truncated_files() {
+        let bytes = build_xcursor(&[(24, 2, 0), (24, 2, 0)]);
+        // the toc has two entries of 12 bytes after the 16 bytes of the header
+        for len in [4, 8, 15, 16, 27, 39, 40, 41, 76, bytes.len() - 1] {
+            assert_invalid(&bytes[..len], "unexpected end of file");
+        }
+    }
+    #[test]
+    fn rejects_overflowing_offsets() {
+        let bytes = build_xcursor(&[(24, 2, 0)]);
+        // header size, toc count, chunk position and chunk header size
+        for offset in [4, 12, 24, 28] {
+            assert_invalid(&patch(&bytes, offset, u32::MAX), "unexpected end of file");
+        }
+        // largest image allowed by the spec
+        let huge = patch(&patch(&bytes, 28 + 16, 0x7fff), 28 + 20, 0x7fff);
+        assert_invalid(&huge, "image data is larger than the file");
+        assert_invalid(&patch(&bytes, 28, 4), "image chunk header is too small");
+    }
+    #[test]
+    fn rejects_overlapping_chunks() {
+        // 64 toc entries pointing to the same chunk would decode it 64 times
+        let mut bytes = build_xcursor(&[(24, 4, 0)]);
+        let chunk = bytes.split_off(28);
+        let entry = patch(&bytes[16..28], 8, 16 + 64 * 12);
+        bytes.truncate(16);
+        bytes = patch(&bytes, 12, 64);
+        bytes.extend(entry.repeat(64));
+        bytes.extend(chunk);
+        assert_invalid(&bytes, "image data is larger than the file");
+    }
+}
@@ -0,0 +1,127 @@
+#!/usr/bin/env python3
+"""Generates the `.ico`/`.cur` fixtures used by the tests of `src/ico.rs`.
+
+Run it from any directory with `python3 generate.py`; it only needs the standard library
+and writes the files next to itself. The output is deterministic, so the committed files
+can be checked with `git diff` after running it.
+
+All the fixtures are the same 4x4 image: a red top row, a transparent bottom-right pixel
+and blue everywhere else (see `check_fixture_image` in `src/ico.rs`).
+
+* `bmp.ico`: an icon with five BMP entries, one per bit depth the decoder supports:
+  32-bit BGRA, 24-bit BGR, 8-bit, 4-bit and 1-bit paletted. Only the 32-bit entry has
+  alpha; the others get their transparent pixel from the AND mask.
+* `png.ico`: an icon with a single PNG entry (8-bit RGBA).
+* `cursor.cur`: a cursor with a single 32-bit BMP entry and the hotspot (1, 2).
+"""
+import os
+import struct
+import zlib
+
+SIZE = 4
+RED = (255, 0, 0, 255)
+BLUE = (0, 0, 255, 255)
+CLEAR = (0, 0, 0, 0)
+
+
+def pixel(x, y):
+    if y == 0:
+        return RED
+    if (x, y) == (SIZE - 1, SIZE - 1):
+        return CLEAR
+    return BLUE
+
+
+def padded(row):
+    """BMP rows are padded to a multiple of 4 bytes."""
+    return row + b"\0" * (-len(row) % 4)
+
+
+def bmp(bit_count):
+    """A BMP payload without the file header, as stored in `.ico` files.
+
+    The height of the header is doubled, because the XOR (color) bitmap is followed by
+    the AND (transparency) mask. Rows are stored bottom-up.
+    """
+    rows = range(SIZE - 1, -1, -1)
+    palette = b""
+    if bit_count == 32:
+        xor = b"".join(
+            bytes((b, g, r, a)) for y in rows for x in range(SIZE) for (r, g, b, a) in [pixel(x, y)]
+        )
+    elif bit_count == 24:
+        xor = b"".join(
+            padded(b"".join(bytes((b, g, r)) for x in range(SIZE) for (r, g, b, _) in [pixel(x, y)]))
+            for y in rows
+        )
+    else:
+        # the palette has all 2^bit_count entries (BGRX), unused ones are black. The 1-bit
+        # palette has no room for black, so it is only red and blue.
+        colors = [RED, BLUE] if bit_count == 1 else [(0, 0, 0, 0), RED, BLUE]
+        colors += [(0, 0, 0, 0)] * ((1 << bit_count) - len(colors))
+        palette = b"".join(bytes((b, g, r, 0)) for (r, g, b, _) in colors)
+
+        def index(x, y):
+            # the transparent pixel is masked, so it uses the first entry
+            color = pixel(x, y)
+            return 0 if color == CLEAR else colors.index(color)
+
+        xor = b""
+        for y in rows:
+            bits = 0
+            for x in range(SIZE):
+                bits = (bits << bit_count) | index(x, y)
+            row_bits = SIZE * bit_count
+            bits <<= -row_bits % 8
+            xor += padded(bits.to_bytes((row_bits + 7) // 8, "big"))
+    # 1 means transparent, the most significant bit is the leftmost pixel
+    mask = b"".join(
+        padded(bytes([sum(0x80 >> x for x in range(SIZE) if pixel(x, y) == CLEAR)])) for y in rows
+    )
+    header = struct.pack(
+        "<IiiHHIIiiII", 40, SIZE, SIZE * 2, 1, bit_count, 0, len(xor) + len(mask), 0, 0, 0, 0
+    )
+    return header + palette + xor + mask
+
+
+def png():
+    def chunk(kind, data):
+        return struct.pack(">I", len(data)) + kind + data + struct.pack(">I", zlib.crc32(kind + data))
+
+    raw = b"".join(
+        b"\0" + b"".join(bytes(pixel(x, y)) for x in range(SIZE)) for y in range(SIZE)
+    )
+    return (
+        b"\x89PNG\r\n\x1a\n"
+        + chunk(b"IHDR", struct.pack(">IIBBBBB", SIZE, SIZE, 8, 6, 0, 0, 0))
+        + chunk(b"IDAT", zlib.compress(raw))
+        + chunk(b"IEND", b"")
+    )
+
+
+def ico(kind, entries):
+    """An `.ico` (kind 1) or `.cur` (kind 2) file.
+
+    `entries` are (payload, planes, bit count). For cursors, the planes and bit count
+    fields are the x and y of the hotspot.
+    """
+    header = struct.pack("<HHH", 0, kind, len(entries))
+    offset = len(header) + 16 * len(entries)
+    directory = b""
+    for payload, planes, bit_count in entries:
+        directory += struct.pack("<BBBBHHII", SIZE, SIZE, 0, 0, planes, bit_count, len(payload), offset)
+        offset += len(payload)
+    return header + directory + b"".join(payload for payload, _, _ in entries)
+
+
+FIXTURES = {
+    "bmp.ico": ico(1, [(bmp(bits), 1, bits) for bits in (32, 24, 8, 4, 1)]),
+    "png.ico": ico(1, [(png(), 1, 32)]),
+    "cursor.cur": ico(2, [(bmp(32), 1, 2)]),
+}
+
+if __name__ == "__main__":
+    directory = os.path.dirname(os.path.abspath(__file__))
+    for name, contents in FIXTURES.items():
+        with open(os.path.join(directory, name), "wb") as file:
+            file.write(contents)
@@ -0,0 +1,583 @@
+//! Tests on the null platform, using `glfw_rust::testing`.
+//!
+//! Run with `cargo test -p glfw_rust --features testing`.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glfw_rust::testing::*;
+use glfw_rust::*;
+
+fn visible() -> WindowConfig<'static> {
+    WindowConfig {
+        visible: Some(true),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn window_lifecycle() {
+    let el = NullEventLoop::new().unwrap();
+    let window = el.window(WindowConfig::default()).unwrap();
+    let id = window.id();
+    assert_eq!(
+        window.get_size(),
+        (TEST_WINDOW_SIZE.0 as i32, TEST_WINDOW_SIZE.1 as i32)
+    );
+    el.drain_events();
+
+    window.set_size(100, 50);
+    window.set_pos(10, 20);
+    let events = el.drain_events();
+    assert!(events.contains(&Event::Size {
+        window: id,
+        width: 100,
+        height: 50
+    }));
+    assert!(events.contains(&Event::FramebufferSize {
+        window: id,
+        width: 100,
+        height: 50
+    }));
+    assert!(events.contains(&Event::Pos {
+        window: id,
+        x: 10,
+        y: 20
+    }));
+
+    assert!(!window.should_close());
+    window.set_should_close(true);
+    assert!(window.should_close());
+
+    drop(window);
+    assert!(el.drain_events().is_empty());
+}
+
+#[test]
+fn iconify_and_maximize() {
+    let el = NullEventLoop::new().unwrap();
+    let window = el.window(visible()).unwrap();
+    let id = window.id();
+    el.drain_events();
+
+    window.iconify();
+    assert!(window.get_iconified());
+    window.restore();
+    assert!(!window.get_iconified());
+    window.maximize();
+    assert!(window.get_maximized());
+    assert_eq!(
+        el.drain_events()
+            .into_iter()
+            .filter(|event| matches!(event, Event::Iconify { .. } | Event::Maximize { .. }))
+            .collect::<Vec<_>>(),
+        [
+            Event::Iconify {
+                window: id,
+                iconified: true
+            },
+            Event::Iconify {
+                window: id,
+                iconified: false
+            },
+            Event::Maximize {
+                window: id,
+                maximized: true
+            },
+        ]
+    );
+}
+
+#[test]
+fn focus_moves_between_windows() {
+    let el = NullEventLoop::new().unwrap();
+    let first = el.window(visible()).unwrap();
+    let second = el.window(visible()).unwrap();
+    first.focus();
+    el.drain_events();
+
+    second.focus();
+    assert!(second.get_focused());
+    assert!(!first.get_focused());
+    let events = el.drain_events();
+    assert!(events.contains(&Event::Focus {
+        window: first.id(),
+        focused: false
+    }));
+    assert!(events.contains(&Event::Focus {
+        window: second.id(),
+        focused: true
+    }));
+}
+
+#[test]
+fn injected_events_keep_order() {
+    let el = NullEventLoop::new().unwrap();
+    let window = el.window(WindowConfig::default()).unwrap();
+    el.drain_events();
+    let injected = [
+        Event::Char {
+            window: window.id(),
+            codepoint: 'a',
+        },
+        Event::Close {
+            window: window.id(),
+        },
+    ];
+    for event in injected.clone() {
+        el.inject_event(event);
+    }
+    assert_eq!(el.drain_events(), injected);
+    assert!(el.drain_events().is_empty());
+}
+
+#[test]
+fn event_loop_can_be_reinitialized() {
+    for _ in 0..2 {
+        let el = NullEventLoop::new().unwrap();
+        assert_eq!(el.get_platform(), Platform::Null);
+        let window = el.window(WindowConfig::default()).unwrap();
+        drop(window);
+    }
+}
+
+#[test]
+fn monitor_liveness() {
+    let el = NullEventLoop::new().unwrap();
+    let primary = el
+        .get_primary_monitor()
+        .expect("null platform has a monitor");
+    assert!(el.is_monitor_alive(primary));
+    assert_eq!(el.get_monitors().first(), Some(&primary));
+    assert!(!el.get_monitor_name(primary).unwrap().is_empty());
+
+    // never returned by glfw, so it is not tracked.
+    let unknown = MonitorId::new(std::ptr::NonNull::dangling().as_ptr()).unwrap();
+    assert!(!el.is_monitor_alive(unknown));
+    assert!(el.get_monitor_name(unknown).is_err());
+    let error = Window::new(
+        el.el().clone(),
+        WindowConfig {
+            client_api: Some(ClientApi::NoAPI),
+            ..Default::default()
+        },
+        100,
+        100,
+        "full screen",
+        Some(unknown),
+        None,
+    )
+    .err()
+    .expect("window creation on a dead monitor must fail");
+    assert_eq!(error.code, ErrorCode::PlatformError);
+}
+
+#[test]
+fn context_current_across_threads() {
+    let el = NullEventLoop::new().unwrap();
+    let no_context = el.window(WindowConfig::default()).unwrap();
+    assert!(!no_context.is_gl_window());
+    assert_eq!(
+        no_context.try_make_current().unwrap_err().code,
+        ErrorCode::NoWindowContext
+    );
+
+    let Some(window) = el.osmesa_window(WindowConfig::default()) else {
+        return;
+    };
+    window.make_current();
+    assert!(window.is_current_on_current_thread());
+    assert_eq!(el.get_any_current(), Some(window.id()));
+
+    let proxy: WindowProxy = (*window).clone();
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            assert!(proxy.is_current_somewhere());
+            assert!(!proxy.is_current_on_current_thread());
+            assert_eq!(
+                proxy.try_make_current().unwrap_err().code,
+                ErrorCode::Crate(CrateError::ContextCurrentElsewhere)
+            );
+        })
+        .join()
+        .unwrap();
+    });
+
+    window.make_uncurrent();
+    assert!(!window.is_current_somewhere());
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            proxy.try_make_current().unwrap();
+            assert!(proxy.is_current_on_current_thread());
+            proxy.make_uncurrent();
+        })
+        .join()
+        .unwrap();
+    });
+    assert!(!window.is_current_somewhere());
+}
+
+#[test]
+fn errors_reach_callback_events_and_history() {
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let errors_in_callback = errors.clone();
+    let el = NullEventLoop::with_config(EventLoopConfig {
+        on_error: Some(Box::new(move |error| {
+            errors_in_callback.borrow_mut().push(error)
+        })),
+        error_events: true,
+        error_history: 4,
+        ..Default::default()
+    })
+    .unwrap();
+    let window = el.window(WindowConfig::default()).unwrap();
+    el.drain_events();
+
+    // 0 is not a valid window attribute
+    let error = el
+        .checked(|| unsafe { glfw_rust_sys::glfwGetWindowAttrib(window.id().get_ptr(), 0) })
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidEnum);
+    assert!(error.is_usage_error());
+    assert!(!error.is_transient());
+    let caller = error.context.expect("checked sets the caller");
+    assert!(caller.file().ends_with("null_platform.rs"));
+    assert!(error.to_string().contains("InvalidEnum"));
+
+    assert_eq!(
+        errors.borrow().last().map(|error| error.code),
+        Some(ErrorCode::InvalidEnum)
+    );
+
+    assert!(el.drain_events().iter().any(|event| matches!(
+        event,
+        Event::Error(GlfwError {
+            code: ErrorCode::InvalidEnum,
+            ..
+        })
+    )));
+
+    let history = el.recent_errors();
+    assert_eq!(history.capacity(), 4);
+    let record = history.iter().last().expect("error was recorded");
+    assert_eq!(record.error.code, ErrorCode::InvalidEnum);
+    assert_eq!(record.thread, std::thread::current().id());
+    assert_eq!(record.caller, Some(caller));
+}
+
+#[test]
+fn custom_error_callback_disables_history() {
+    let el = NullEventLoop::with_config(EventLoopConfig {
+        error_callback: Some(default_error_callback),
+        error_history: 4,
+        ..Default::default()
+    })
+    .unwrap();
+    let window = el.window(WindowConfig::default()).unwrap();
+    el.checked(|| unsafe { glfw_rust_sys::glfwGetWindowAttrib(window.id().get_ptr(), 0) })
+        .unwrap_err();
+    let history = el.recent_errors();
+    assert_eq!(history.capacity(), 0);
+    assert!(history.is_empty());
+}
+
+#[test]
+fn panicking_error_handler_is_caught() {
+    let el = NullEventLoop::with_config(EventLoopConfig {
+        on_error: Some(Box::new(|error| panic!("handler panicked on {error}"))),
+        error_events: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let window = el.window(WindowConfig::default()).unwrap();
+    el.drain_events();
+    let error = el
+        .checked(|| unsafe { glfw_rust_sys::glfwGetWindowAttrib(window.id().get_ptr(), 0) })
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidEnum);
+    // the event is queued before the handler runs, so it survives the panic.
+    assert!(el
+        .drain_events()
+        .iter()
+        .any(|event| matches!(event, Event::Error(_))));
+}
+
+#[test]
+fn crate_errors_instead_of_panics() {
+    let el = NullEventLoop::new().unwrap();
+    let error = Window::new(
+        el.el().clone(),
+        WindowConfig {
+            client_api: Some(ClientApi::NoAPI),
+            ..Default::default()
+        },
+        100,
+        100,
+        "null\0byte",
+        None,
+        None,
+    )
+    .err()
+    .expect("title with a null byte must fail");
+    assert_eq!(error.code, ErrorCode::Crate(CrateError::InteriorNul));
+
+    let error = Cursor::try_new_from_pixels(el.el().clone(), 2, 2, &[0; 4], 0, 0).unwrap_err();
+    assert_eq!(error.code, ErrorCode::Crate(CrateError::InvalidPixelBuffer));
+    let cursor = Cursor::try_new_from_pixels(el.el().clone(), 2, 2, &[255; 16], 0, 0).unwrap();
+
+    let error = AnimatedCursor::try_new(vec![]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+    let error = AnimatedCursor::try_new(vec![(cursor, 0.0)]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+
+    let window = el.window(WindowConfig::default()).unwrap();
+    let icon = Image {
+        width: 2,
+        height: 2,
+        pixels: vec![0; 4],
+    };
+    let error = window.set_icon(&[icon]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::Crate(CrateError::InvalidPixelBuffer));
+    let error = el.clipboard().set_text("null\0byte").unwrap_err();
+    assert_eq!(error.code, ErrorCode::Crate(CrateError::InteriorNul));
+}
+
+#[test]
+fn std_cursors_are_shared() {
+    let el = NullEventLoop::new().unwrap();
+    let window = el.window(WindowConfig::default()).unwrap();
+    let arrow = el.el().std_cursor(StdCursor::Arrow).unwrap();
+    assert!(Rc::ptr_eq(
+        &arrow,
+        &el.el().std_cursor(StdCursor::Arrow).unwrap()
+    ));
+    window.set_std_cursor(StdCursor::Arrow).unwrap();
+    drop(arrow);
+    // the window keeps the cursor alive, so it is still cached.
+    let arrow = el.el().std_cursor(StdCursor::Arrow).unwrap();
+    assert_eq!(Rc::strong_count(&arrow), 2);
+    window.set_cursor(None);
+    assert_eq!(Rc::strong_count(&arrow), 1);
+}
+
+#[test]
+fn pointer_lock_is_released_on_focus_loss() {
+    let el = NullEventLoop::new().unwrap();
+    let first = el.window(visible()).unwrap();
+    let second = el.window(visible()).unwrap();
+    first.focus();
+
+    let lock = first.lock_pointer().unwrap();
+    assert!(lock.is_active());
+    assert_eq!(first.get_cursor_mode(), CursorMode::Disabled);
+    assert!(first.lock_pointer().is_err());
+
+    second.focus();
+    assert!(!lock.is_active());
+    assert_eq!(first.get_cursor_mode(), CursorMode::Normal);
+
+    // the stale guard must not release a newer lock of the same window.
+    first.focus();
+    let newer = first.lock_pointer().unwrap();
+    assert!(!lock.is_active());
+    drop(lock);
+    assert!(newer.is_active());
+    assert_eq!(first.get_cursor_mode(), CursorMode::Disabled);
+    drop(newer);
+    assert_eq!(first.get_cursor_mode(), CursorMode::Normal);
+}
+
+#[cfg(feature = "osmesa")]
+#[test]
+fn osmesa_readback() {
+    const GL_COLOR_BUFFER_BIT: u32 = 0x4000;
+    const GL_DEPTH_BUFFER_BIT: u32 = 0x0100;
+    const GL_SCISSOR_TEST: u32 = 0x0C11;
+    type ClearColor = unsafe extern "system" fn(f32, f32, f32, f32);
+    type Clear = unsafe extern "system" fn(u32);
+    type Enable = unsafe extern "system" fn(u32);
+    type Scissor = unsafe extern "system" fn(i32, i32, i32, i32);
+
+    let (width, height) = TEST_WINDOW_SIZE;
+    let el = NullEventLoop::new().unwrap();
+    let Some(window) = el.osmesa_window(WindowConfig {
+        depth_bits: Some(24),
+        ..Default::default()
+    }) else {
+        return;
+    };
+    let guard = window.make_current_scoped();
+    let info = window.context_info();
+    assert!(!info.is_gles);
+    assert!(info.version_major >= 1);
+    assert!(!info.vendor.is_empty());
+    assert_eq!(info.robustness, window.get_context_robustness());
+    let loader = guard.loader();
+    loader
+        .require(&["glClearColor", "glClear", "glEnable", "glScissor"])
+        .unwrap();
+    unsafe {
+        let clear_color: ClearColor = std::mem::transmute(loader.get("glClearColor"));
+        let clear: Clear = std::mem::transmute(loader.get("glClear"));
+        let enable: Enable = std::mem::transmute(loader.get("glEnable"));
+        let scissor: Scissor = std::mem::transmute(loader.get("glScissor"));
+        // red everywhere, then blue in the bottom half (gl's origin is bottom left).
+        clear_color(1.0, 0.0, 0.0, 1.0);
+        clear(GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT);
+        enable(GL_SCISSOR_TEST);
+        scissor(0, 0, width as i32, height as i32 / 2);
+        clear_color(0.0, 0.0, 1.0, 1.0);
+        clear(GL_COLOR_BUFFER_BIT);
+    }
+
+    let image = window.read_color_buffer().unwrap();
+    assert_eq!((image.width, image.height), (width, height));
+    let (top, bottom) = image.pixels.split_at(image.pixels.len() / 2);
+    assert!(top.chunks_exact(4).all(|pixel| pixel == [255, 0, 0, 255]));
+    assert!(bottom
+        .chunks_exact(4)
+        .all(|pixel| pixel == [0, 0, 255, 255]));
+
+    let depth = window.read_depth_buffer().unwrap();
+    assert_eq!((depth.width, depth.height), (width, height));
+    assert_eq!(depth.values.len(), (width * height) as usize);
+    // cleared to the default depth of 1.0, so every value is the same.
+    assert!(depth.values.iter().all(|&value| value == depth.values[0]));
+    drop(guard);
+}
+
+#[cfg(feature = "osmesa")]
+#[test]
+fn shared_context_outlives_parent_while_current() {
+    let el = NullEventLoop::new().unwrap();
+    let Some(window) = el.osmesa_window(WindowConfig::default()) else {
+        return;
+    };
+    let shared = window.new_shared_context().unwrap();
+    let (current_tx, current_rx) = std::sync::mpsc::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let worker = std::thread::spawn(move || {
+        shared.try_make_current().unwrap();
+        current_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        shared.make_uncurrent();
+        shared
+    });
+    current_rx.recv().unwrap();
+    // the parent can go away while the worker still uses the share group.
+    drop(window);
+    let shared = {
+        release_tx.send(()).unwrap();
+        worker.join().unwrap()
+    };
+    assert!(shared.is_share_group_alive());
+    // dropped on the main thread while current on a worker: destroying it must wait.
+    let proxy = WindowProxy::clone(&shared);
+    let (shared_tx, shared_rx) = std::sync::mpsc::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let worker = std::thread::spawn(move || {
+        shared.try_make_current().unwrap();
+        let proxy = WindowProxy::clone(&shared);
+        shared_tx.send(shared).unwrap();
+        release_rx.recv().unwrap();
+        proxy.make_uncurrent();
+    });
+    drop(shared_rx.recv().unwrap());
+    el.drain_events();
+    assert_eq!(
+        proxy.try_make_current().unwrap_err().code,
+        ErrorCode::Crate(CrateError::ContextCurrentElsewhere)
+    );
+    release_tx.send(()).unwrap();
+    worker.join().unwrap();
+    el.drain_events();
+    assert_eq!(
+        proxy.try_make_current().unwrap_err().code,
+        ErrorCode::Crate(CrateError::WindowDead)
+    );
+}
+
+#[cfg(feature = "osmesa")]
+#[test]
+fn window_dropped_while_current_elsewhere_is_destroyed_later() {
+    let el = NullEventLoop::new().unwrap();
+    let Some(window) = el.osmesa_window(WindowConfig::default()) else {
+        return;
+    };
+    let proxy = WindowProxy::clone(&window);
+    let (current_tx, current_rx) = std::sync::mpsc::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let worker = {
+        let proxy = proxy.clone();
+        std::thread::spawn(move || {
+            proxy.try_make_current().unwrap();
+            current_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            // the main thread must not have destroyed it (this would panic on a dead window).
+            proxy.set_should_close(true);
+            assert!(proxy.should_close());
+            proxy.make_uncurrent();
+        })
+    };
+    current_rx.recv().unwrap();
+    drop(window);
+    el.drain_events();
+    assert_eq!(
+        proxy.try_make_current().unwrap_err().code,
+        ErrorCode::Crate(CrateError::ContextCurrentElsewhere)
+    );
+    release_tx.send(()).unwrap();
+    worker.join().unwrap();
+    el.drain_events();
+    assert_eq!(
+        proxy.try_make_current().unwrap_err().code,
+        ErrorCode::Crate(CrateError::WindowDead)
+    );
+}
+
+#[cfg(feature = "osmesa")]
+#[test]
+fn nested_current_guards() {
+    let el = NullEventLoop::new().unwrap();
+    let (Some(a), Some(b)) = (
+        el.osmesa_window(WindowConfig::default()),
+        el.osmesa_window(WindowConfig::default()),
+    ) else {
+        return;
+    };
+    // dropped in order, every guard restores the context that was current before it.
+    a.make_current();
+    {
+        let _b = b.make_current_scoped();
+        assert_eq!(el.get_any_current(), Some(b.id()));
+        {
+            let _a = a.make_current_scoped();
+            assert_eq!(el.get_any_current(), Some(a.id()));
+        }
+        assert_eq!(el.get_any_current(), Some(b.id()));
+    }
+    assert_eq!(el.get_any_current(), Some(a.id()));
+    a.make_uncurrent();
+
+    // the outer guard is dropped first: the inner window stays current until its guard
+    // is dropped, which then restores what was current before the outer guard (nothing).
+    let outer = a.make_current_scoped();
+    let inner = b.make_current_scoped();
+    drop(outer);
+    assert_eq!(el.get_any_current(), Some(b.id()));
+    drop(inner);
+    assert_eq!(el.get_any_current(), None);
+
+    // the middle guard is dropped first: the innermost guard restores what was current
+    // before the middle guard.
+    let outer = a.make_current_scoped();
+    let middle = b.make_current_scoped();
+    let inner = a.make_current_scoped();
+    drop(middle);
+    assert_eq!(el.get_any_current(), Some(a.id()));
+    drop(inner);
+    assert_eq!(el.get_any_current(), Some(a.id()));
+    // the outer guard is still usable, as its window is current.
+    assert!(!outer.get_proc_addr("glClear").is_null());
+    drop(outer);
+    assert_eq!(el.get_any_current(), None);
+}
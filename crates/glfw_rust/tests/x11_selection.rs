@@ -0,0 +1,93 @@
+//! Tests the x11 primary selection against a private Xvfb server.
+//!
+//! This runs without the libtest harness (see Cargo.toml), so that we can point `DISPLAY`
+//! at the server before any other thread exists. If `Xvfb` is not installed, the test is
+//! reported as skipped.
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn main() {
+    let Some((_xvfb, display)) = x11::start_xvfb() else {
+        println!("test primary_selection_round_trip ... skipped (Xvfb is not available)");
+        return;
+    };
+    // there are no other threads yet, so this can't race with a getenv.
+    std::env::set_var("DISPLAY", &display);
+    x11::primary_selection_round_trip();
+    println!("test primary_selection_round_trip ... ok");
+}
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn main() {
+    println!("test primary_selection_round_trip ... skipped (x11 only)");
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod x11 {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+
+    use glfw_rust::*;
+
+    /// Kills the Xvfb server when dropped.
+    pub struct Xvfb(Child);
+    impl Drop for Xvfb {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+    /// Starts Xvfb on a free display and returns it with the display name (eg: `:97`).
+    ///
+    /// With `-displayfd`, Xvfb picks a free display number itself and writes it to the fd
+    /// once it is ready to accept connections.
+    pub fn start_xvfb() -> Option<(Xvfb, String)> {
+        let child = Command::new("Xvfb")
+            .args([
+                "-displayfd",
+                "1",
+                "-screen",
+                "0",
+                "640x480x24",
+                "-nolisten",
+                "tcp",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut xvfb = Xvfb(child);
+        let stdout = xvfb.0.stdout.take()?;
+        let mut display = String::new();
+        BufReader::new(stdout).read_line(&mut display).ok()?;
+        let display = display.trim();
+        if display.is_empty() {
+            return None;
+        }
+        Some((xvfb, format!(":{display}")))
+    }
+
+    pub fn primary_selection_round_trip() {
+        let el = EventLoop::init(EventLoopConfig {
+            platform: Some(Platform::X11),
+            ..Default::default()
+        })
+        .expect("failed to init glfw on Xvfb");
+        // glfw needs a window to own the selection
+        let _window = Window::new(
+            el.clone(),
+            WindowConfig {
+                visible: Some(false),
+                client_api: Some(ClientApi::NoAPI),
+                ..Default::default()
+            },
+            64,
+            64,
+            "selection",
+            None,
+            None,
+        )
+        .expect("failed to create window");
+        el.set_primary_selection("hello primary")
+            .expect("failed to set primary selection");
+        assert_eq!(el.get_primary_selection().unwrap(), "hello primary");
+    }
+}
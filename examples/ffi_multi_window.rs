@@ -45,10 +45,11 @@ fn main() {
 
             panic!(
                 "Error: {:?}\n",
-                description
-                    .is_null()
-                    .then_some(c"")
-                    .unwrap_or_else(|| CStr::from_ptr(description))
+                if description.is_null() {
+                    c""
+                } else {
+                    CStr::from_ptr(description)
+                }
             );
         }
 
@@ -111,10 +112,11 @@ fn main() {
 
                 println!(
                     "Error: {:?}\n",
-                    description
-                        .is_null()
-                        .then_some(c"")
-                        .unwrap_or_else(|| CStr::from_ptr(description))
+                    if description.is_null() {
+                        c""
+                    } else {
+                        CStr::from_ptr(description)
+                    }
                 );
                 glfwTerminate();
                 panic!();
@@ -125,10 +127,9 @@ fn main() {
             glfwMakeContextCurrent(win);
 
             let ctx = glow::Context::from_loader_function_cstr(|s| {
-                let result = glfwGetProcAddress(s.as_ptr())
+                glfwGetProcAddress(s.as_ptr())
                     .map(|p| p as _)
-                    .unwrap_or(std::ptr::null());
-                result
+                    .unwrap_or(std::ptr::null())
             });
 
             ctx.clear_color(colors[i].r, colors[i].g, colors[i].b, 1.0);
@@ -23,8 +23,8 @@ fn main() {
         None,
     )
     .unwrap();
-    window.make_current();
-    let ctx = unsafe { glow::Context::from_loader_function(|s| window.get_proc_addr(s)) };
+    let current = window.make_current_scoped();
+    let ctx = unsafe { glow::Context::from_loader_function(|s| current.get_proc_addr(s)) };
     unsafe { ctx.clear_color(0.95, 0.32, 0.11, 1.0) };
     // To print fps every second
     let mut fps_counter = 0;
@@ -45,8 +45,8 @@ fn main() {
             fps_counter = 0;
             fps_reset = std::time::Instant::now();
         }
-        window.swap_buffers();
+        current.swap_buffers();
     }
     // drop will automatically do this, but might as well follow good practice
-    window.make_uncurrent();
+    drop(current);
 }